@@ -0,0 +1,142 @@
+use crate::position::calculate_position_by_offset;
+
+use super::routes::aircraft::StatusDto;
+
+/// Maximum speed that aircraft are extrapolated with in _km/h_, so that implausible speeds
+/// can't move them arbitrarily far
+pub const MAX_SPEED: f32 = 1200.0;
+/// Turn rates below this value (in _turns/min_) are considered as flying straight
+const MIN_TURN_RATE: f32 = 0.01;
+
+/// Projects a status forward from its last course, speed, vertical speed and turn rate to given
+/// timestamp ("dead reckoning"). The turn rate is assumed to be constant, so turning aircraft
+/// are moved on a circle.
+///
+/// # Arguments
+///
+/// * `status_dto` - The status to extrapolate
+/// * `time_stamp` - The timestamp to extrapolate to
+///
+/// # Returns
+///
+/// The extrapolated status with `StatusDto::extrapolation_age` set or the unchanged status, if
+/// it can't be extrapolated (unknown course or speed) or is not older than `time_stamp`.
+///
+/// # Examples
+///
+/// * test `extrapolation::extrapolates_straight_flight`
+/// * test `extrapolation::extrapolates_turning_flight`
+pub fn extrapolate(mut status_dto: StatusDto, time_stamp: u64) -> StatusDto {
+    let age = time_stamp.saturating_sub(status_dto.time_stamp);
+
+    let (Some(course), Some(speed)) = (status_dto.course, status_dto.speed) else {
+        return status_dto;
+    };
+
+    if age == 0 {
+        return status_dto;
+    }
+
+    let seconds = age as f32;
+    let speed = f32::from(speed).min(MAX_SPEED) / 3600.0; /* km/s */
+    let initial_course = f32::from(course).to_radians();
+    let turn_rate = status_dto.turn_rate.unwrap_or(0.0);
+
+    let (north, east, final_course) = if turn_rate.abs() < MIN_TURN_RATE {
+        (
+            speed * seconds * initial_course.cos(),
+            speed * seconds * initial_course.sin(),
+            initial_course,
+        )
+    } else {
+        /* Constant turn rate: integrate the velocity along the arc */
+        let angular_speed = (turn_rate * 360.0 / 60.0).to_radians(); /* rad/s */
+        let final_course = initial_course + angular_speed * seconds;
+        let radius = speed / angular_speed;
+
+        (
+            radius * (final_course.sin() - initial_course.sin()),
+            radius * (initial_course.cos() - final_course.cos()),
+            final_course,
+        )
+    };
+
+    status_dto.position = calculate_position_by_offset(&status_dto.position, north, east);
+    status_dto.course = Some(final_course.to_degrees().rem_euclid(360.0).round() as u16 % 360);
+
+    if let (Some(altitude), Some(vertical_speed)) = (status_dto.altitude, status_dto.vertical_speed)
+    {
+        status_dto.altitude = Some(
+            (f32::from(altitude) + vertical_speed * seconds).clamp(0.0, f32::from(u16::MAX)) as u16,
+        );
+    }
+
+    status_dto.extrapolation_age = Some(age);
+    status_dto
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{aprs::Status, position::calculate_distance};
+
+    use super::*;
+
+    #[test]
+    fn extrapolates_straight_flight() {
+        /* 360 km/h eastwards for 60 seconds -> 6 km */
+        let status_dto = StatusDto::from(&create_status(Some(90), Some(360), None), None);
+
+        let result = extrapolate(status_dto.clone(), status_dto.time_stamp + 60);
+
+        assert_eq!(result.extrapolation_age, Some(60));
+        assert_eq!(result.course, Some(90));
+        assert_eq!(result.altitude, Some(940));
+        assert!((calculate_distance(&status_dto.position, &result.position) - 6.0).abs() < 0.01);
+        assert!((result.position.latitude - status_dto.position.latitude).abs() < 0.0001);
+        assert!(result.position.longitude > status_dto.position.longitude);
+
+        /* Nothing to extrapolate */
+        let result = extrapolate(status_dto.clone(), status_dto.time_stamp);
+        assert!(result.extrapolation_age.is_none());
+
+        let status_dto = StatusDto::from(&create_status(None, Some(360), None), None);
+        let result = extrapolate(status_dto.clone(), status_dto.time_stamp + 60);
+        assert!(result.extrapolation_age.is_none());
+    }
+
+    #[test]
+    fn extrapolates_turning_flight() {
+        /* One full circle per minute ends up at the start again */
+        let status_dto = StatusDto::from(&create_status(Some(0), Some(100), Some(1.0)), None);
+
+        let result = extrapolate(status_dto.clone(), status_dto.time_stamp + 60);
+
+        assert_eq!(result.course, Some(0));
+        assert!(calculate_distance(&status_dto.position, &result.position) < 0.01);
+
+        /* Half a circle to the right ends up east of the start, heading south */
+        let result = extrapolate(status_dto.clone(), status_dto.time_stamp + 30);
+
+        assert_eq!(result.course, Some(180));
+        assert!(result.position.longitude > status_dto.position.longitude);
+
+        /* Diameter of the circle is the distance of half a minute at 100 km/h, divided by π/2 */
+        let expected_distance = 100.0 / 120.0 * 2.0 / std::f32::consts::PI;
+        assert!(
+            (calculate_distance(&status_dto.position, &result.position) - expected_distance).abs()
+                < 0.01
+        );
+    }
+
+    fn create_status(course: Option<u16>, speed: Option<u16>, turn_rate: Option<f32>) -> Status {
+        Status {
+            speed,
+            vertical_speed: Some(-1.0),
+            altitude: Some(1000),
+            turn_rate,
+            course,
+            time_stamp: 1_700_000_000,
+            ..Status::create_for_test("AB1234", 48.858222, 2.2945)
+        }
+    }
+}
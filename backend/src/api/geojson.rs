@@ -0,0 +1,432 @@
+use axum::{
+    http::{header, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::position::{Polygon, Position};
+
+use super::{routes::aircraft::StatusDto, state::TrackPoint};
+
+/// Media type of GeoJSON
+const MEDIA_TYPE_GEOJSON: &str = "application/geo+json";
+
+/// Output format of a route
+#[derive(Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    /// Regular JSON representation
+    #[default]
+    Json,
+    /// GeoJSON representation
+    GeoJson,
+}
+
+/// Query parameters for selecting the output format
+#[derive(Deserialize)]
+pub struct FormatParameters {
+    /// Requested output format, e.g. "geojson"
+    format: Option<Format>,
+}
+
+impl Format {
+    /// Returns the requested output format, either by _format_ query parameter or by
+    /// _Accept_ header. The query parameter takes precedence.
+    ///
+    /// # Arguments
+    ///
+    /// * `parameters` - The query parameters of the request
+    /// * `headers` - The headers of the request
+    ///
+    /// # Examples
+    ///
+    /// * test `geojson::format_is_negotiated`
+    pub fn negotiate(parameters: &FormatParameters, headers: &HeaderMap) -> Format {
+        if let Some(format) = parameters.format {
+            return format;
+        }
+
+        let accepts_geojson = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .any(|value| value.contains(MEDIA_TYPE_GEOJSON));
+
+        if accepts_geojson {
+            Format::GeoJson
+        } else {
+            Format::Json
+        }
+    }
+}
+
+/// GeoJSON `FeatureCollection`
+#[derive(Serialize)]
+#[serde(tag = "type")]
+pub struct FeatureCollection<'a> {
+    /// The features of the collection
+    features: Vec<Feature<'a>>,
+}
+
+/// GeoJSON `Feature`
+#[derive(Serialize)]
+#[serde(tag = "type")]
+pub struct Feature<'a> {
+    /// Geometry of the feature
+    geometry: Geometry,
+    /// Additional information of the feature
+    properties: Properties<'a>,
+}
+
+/// GeoJSON geometry. Coordinates are given as _[longitude, latitude]_ or, if known,
+/// _[longitude, latitude, altitude]_.
+#[derive(Serialize)]
+#[serde(tag = "type", content = "coordinates")]
+pub enum Geometry {
+    Point(Vec<f32>),
+    LineString(Vec<Vec<f32>>),
+}
+
+/// GeoJSON object that describes an area, either a geometry or a `Feature` of it. Coordinates
+/// are given as _[longitude, latitude]_, further values are ignored.
+#[derive(Deserialize)]
+#[serde(tag = "type")]
+pub enum Area {
+    /// Single polygon; the first ring is its boundary, all others are holes
+    Polygon { coordinates: Vec<Vec<Vec<f32>>> },
+    /// Multiple polygons
+    MultiPolygon {
+        coordinates: Vec<Vec<Vec<Vec<f32>>>>,
+    },
+    /// `Feature` with the geometry of the area
+    Feature { geometry: Box<Area> },
+}
+
+/// Properties of a `Feature`
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum Properties<'a> {
+    /// Properties of an aircraft status, same as its regular JSON representation
+    Status(&'a StatusDto),
+    /// Properties of an aircraft track
+    Track {
+        /// Timestamps of the positions of the track
+        time_stamps: Vec<u64>,
+    },
+}
+
+impl<'a> FeatureCollection<'a> {
+    /// Creates a `FeatureCollection` with a _Point_ feature for every given state
+    ///
+    /// # Arguments
+    ///
+    /// * `status_dtos` - The states to convert
+    ///
+    /// # Examples
+    ///
+    /// * test `geojson::converts_states`
+    pub fn from_status_dtos(status_dtos: &'a [StatusDto]) -> Self {
+        let features = status_dtos
+            .iter()
+            .map(|status_dto| Feature {
+                geometry: Geometry::Point(to_coordinates(
+                    &status_dto.position,
+                    status_dto.altitude,
+                )),
+                properties: Properties::Status(status_dto),
+            })
+            .collect();
+
+        Self { features }
+    }
+
+    /// Creates a `FeatureCollection` with a single _LineString_ feature of given track
+    ///
+    /// # Arguments
+    ///
+    /// * `points` - The points of the track, oldest first
+    ///
+    /// # Examples
+    ///
+    /// * test `geojson::converts_track`
+    pub fn from_track(points: &[TrackPoint]) -> Self {
+        let feature = Feature {
+            geometry: Geometry::LineString(
+                points
+                    .iter()
+                    .map(|point| to_coordinates(&point.position, point.altitude))
+                    .collect(),
+            ),
+            properties: Properties::Track {
+                time_stamps: points.iter().map(|point| point.time_stamp).collect(),
+            },
+        };
+
+        Self {
+            features: vec![feature],
+        }
+    }
+}
+
+impl Area {
+    /// Converts the area to polygons
+    ///
+    /// # Returns
+    ///
+    /// The polygons of the area or an error message, if a ring has less than three positions
+    /// or a position is invalid.
+    ///
+    /// # Examples
+    ///
+    /// * test `geojson::converts_area`
+    pub fn into_polygons(self) -> Result<Vec<Polygon>, &'static str> {
+        match self {
+            Area::Polygon { coordinates } => Ok(vec![to_polygon(coordinates)?]),
+            Area::MultiPolygon { coordinates } => coordinates.into_iter().map(to_polygon).collect(),
+            Area::Feature { geometry } => geometry.into_polygons(),
+        }
+    }
+}
+
+impl IntoResponse for FeatureCollection<'_> {
+    fn into_response(self) -> Response {
+        (
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(MEDIA_TYPE_GEOJSON),
+            )],
+            Json(self),
+        )
+            .into_response()
+    }
+}
+
+/// Converts a position to GeoJSON coordinates
+///
+/// # Arguments
+///
+/// * `position` - The position
+/// * `altitude` - Altitude at `position` in _m_, if known
+fn to_coordinates(position: &Position, altitude: Option<u16>) -> Vec<f32> {
+    let mut coordinates = vec![position.longitude, position.latitude];
+
+    if let Some(altitude) = altitude {
+        coordinates.push(f32::from(altitude));
+    }
+
+    coordinates
+}
+
+/// Converts GeoJSON polygon coordinates to a `Polygon`
+///
+/// # Arguments
+///
+/// * `rings` - Rings of _[longitude, latitude]_ coordinates
+fn to_polygon(rings: Vec<Vec<Vec<f32>>>) -> Result<Polygon, &'static str> {
+    if rings.is_empty() {
+        return Err("Polygon has no rings");
+    }
+
+    let rings = rings
+        .into_iter()
+        .map(|ring| {
+            if ring.len() < 3 {
+                return Err("Ring of polygon has less than three positions");
+            }
+
+            ring.into_iter().map(to_position).collect()
+        })
+        .collect::<Result<Vec<Vec<Position>>, &'static str>>()?;
+
+    Ok(Polygon::create(rings))
+}
+
+/// Converts GeoJSON coordinates to a position
+///
+/// # Arguments
+///
+/// * `coordinates` - _[longitude, latitude]_, further values are ignored
+fn to_position(coordinates: Vec<f32>) -> Result<Position, &'static str> {
+    let [longitude, latitude, ..] = coordinates[..] else {
+        return Err("Position has less than two coordinates");
+    };
+
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return Err("Position is out of range");
+    }
+
+    Ok(Position {
+        latitude,
+        longitude,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::aprs::Status;
+
+    use super::*;
+
+    #[test]
+    fn format_is_negotiated() {
+        let mut headers = HeaderMap::new();
+        let no_format = FormatParameters { format: None };
+        let json_format = FormatParameters {
+            format: Some(Format::Json),
+        };
+
+        assert!(Format::negotiate(&no_format, &headers) == Format::Json);
+
+        headers.insert(
+            header::ACCEPT,
+            HeaderValue::from_static("application/geo+json, application/json"),
+        );
+
+        assert!(Format::negotiate(&no_format, &headers) == Format::GeoJson);
+        assert!(Format::negotiate(&json_format, &headers) == Format::Json);
+    }
+
+    #[test]
+    fn converts_states() {
+        let status_dtos = [StatusDto::from(&create_status(Some(1000)), Some(1.5))];
+
+        let result = serde_json::to_value(FeatureCollection::from_status_dtos(&status_dtos));
+
+        assert_eq!(
+            result.unwrap(),
+            json!({
+                "type": "FeatureCollection",
+                "features": [{
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "Point",
+                        "coordinates": [2.0, 48.0, 1000.0]
+                    },
+                    "properties": {
+                        "aircraft": {
+                            "call_sign": "G1",
+                            "registration": "D-6507",
+                            "model": "ASK-21"
+                        },
+                        "position": {
+                            "latitude": 48.0,
+                            "longitude": 2.0
+                        },
+                        "speed": 100,
+                        "vertical_speed": null,
+                        "altitude": 1000,
+                        "turn_rate": null,
+                        "course": 90,
+                        "time_stamp": status_dtos[0].time_stamp,
+                        "age": status_dtos[0].age,
+                        "distance": 1.5
+                    }
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn converts_track() {
+        let points = [
+            TrackPoint {
+                position: Position {
+                    latitude: 48.0,
+                    longitude: 2.0,
+                },
+                altitude: Some(1000),
+                speed: None,
+                vertical_speed: None,
+                course: None,
+                time_stamp: 1,
+            },
+            TrackPoint {
+                position: Position {
+                    latitude: 48.5,
+                    longitude: 2.5,
+                },
+                altitude: None,
+                speed: None,
+                vertical_speed: None,
+                course: None,
+                time_stamp: 2,
+            },
+        ];
+
+        let result = serde_json::to_value(FeatureCollection::from_track(&points));
+
+        assert_eq!(
+            result.unwrap(),
+            json!({
+                "type": "FeatureCollection",
+                "features": [{
+                    "type": "Feature",
+                    "geometry": {
+                        "type": "LineString",
+                        "coordinates": [[2.0, 48.0, 1000.0], [2.5, 48.5]]
+                    },
+                    "properties": {
+                        "time_stamps": [1, 2]
+                    }
+                }]
+            })
+        );
+    }
+
+    #[test]
+    fn converts_area() {
+        let area: Area = serde_json::from_value(json!({
+            "type": "Feature",
+            "geometry": {
+                "type": "MultiPolygon",
+                "coordinates": [
+                    [[[2.0, 48.0], [3.0, 48.0], [3.0, 49.0], [2.0, 48.0]]],
+                    [[[170.0, -10.0], [-170.0, -10.0], [-170.0, 10.0], [170.0, 10.0], [170.0, -10.0]]]
+                ]
+            }
+        }))
+        .unwrap();
+
+        let polygons = area.into_polygons().unwrap();
+
+        assert_eq!(polygons.len(), 2);
+        assert!(polygons[1].contains(&Position {
+            latitude: 0.0,
+            longitude: 179.5,
+        }));
+        assert!(polygons[1].contains(&Position {
+            latitude: 0.0,
+            longitude: -179.5,
+        }));
+
+        let too_short: Area = serde_json::from_value(json!({
+            "type": "Polygon",
+            "coordinates": [[[2.0, 48.0], [3.0, 48.0]]]
+        }))
+        .unwrap();
+        assert!(too_short.into_polygons().is_err());
+
+        let out_of_range: Area = serde_json::from_value(json!({
+            "type": "Polygon",
+            "coordinates": [[[2.0, 48.0], [3.0, 91.0], [3.0, 49.0]]]
+        }))
+        .unwrap();
+        assert!(out_of_range.into_polygons().is_err());
+    }
+
+    fn create_status(altitude: Option<u16>) -> Status {
+        let mut status = Status {
+            speed: Some(100),
+            altitude,
+            course: Some(90),
+            ..Status::create_for_test("AB1234", 48.0, 2.0)
+        };
+        status.aircraft.call_sign = Some(String::from("G1"));
+        status.aircraft.registration = Some(String::from("D-6507"));
+        status.aircraft.model = Some(String::from("ASK-21"));
+        status
+    }
+}
@@ -0,0 +1,293 @@
+use std::fmt::Write;
+
+use axum::{
+    http::{header, HeaderValue},
+    response::{IntoResponse, Response},
+};
+
+use crate::{ogn::AircraftType, time::get_date_time};
+
+use super::routes::aircraft::StatusDto;
+
+/// Media type of KML
+const MEDIA_TYPE_KML: &str = "application/vnd.google-earth.kml+xml";
+/// Interval in which Google Earth should refresh network links (in seconds)
+const REFRESH_INTERVAL_SECONDS: u16 = 10;
+/// Base url of the Google Earth icons
+const ICON_BASE_URL: &str = "https://maps.google.com/mapfiles/kml/shapes/";
+
+/// A rendered KML document
+pub struct Document {
+    /// The KML content
+    content: String,
+    /// File name that clients should use when saving the document
+    file_name: &'static str,
+}
+
+impl Document {
+    /// Renders a KML document with a 3D placemark for every given state. The placemark icon
+    /// depends on the aircraft type and is rotated by the course of the aircraft.
+    ///
+    /// # Arguments
+    ///
+    /// * `status_dtos` - The states to render
+    ///
+    /// # Examples
+    ///
+    /// * test `kml::renders_states`
+    pub fn from_status_dtos(status_dtos: &[StatusDto]) -> Document {
+        let mut placemarks = String::new();
+
+        for status_dto in status_dtos {
+            let name = status_dto
+                .aircraft
+                .call_sign
+                .as_ref()
+                .or(status_dto.aircraft.registration.as_ref())
+                .or(status_dto.aircraft.model.as_ref())
+                .map_or("Unknown", String::as_str);
+
+            /* Without altitude, we can only put the aircraft on the ground */
+            let (altitude_mode, altitude) = match status_dto.altitude {
+                Some(altitude) => ("absolute", altitude),
+                None => ("clampToGround", 0),
+            };
+
+            /* Writing to a `String` can't fail */
+            let _ = write!(
+                placemarks,
+                "<Placemark><name>{}</name><description>{}</description><Style><IconStyle><heading>{}</heading><Icon><href>{}{}</href></Icon></IconStyle></Style><Point><extrude>1</extrude><altitudeMode>{}</altitudeMode><coordinates>{},{},{}</coordinates></Point></Placemark>",
+                escape(name),
+                escape(&create_description(status_dto)),
+                status_dto.course.unwrap_or(0),
+                ICON_BASE_URL,
+                get_icon_name(status_dto.aircraft_type),
+                altitude_mode,
+                status_dto.position.longitude,
+                status_dto.position.latitude,
+                altitude
+            );
+        }
+
+        Document {
+            content: wrap(&format!(
+                "<Document><name>above_me</name>{placemarks}</Document>"
+            )),
+            file_name: "above_me.kml",
+        }
+    }
+
+    /// Renders a KML document with a network link that refreshes given KML url regularly
+    ///
+    /// # Arguments
+    ///
+    /// * `href` - Url of the KML document to refresh, may be relative to the network link
+    ///
+    /// # Examples
+    ///
+    /// * test `kml::renders_network_link`
+    pub fn from_network_link(href: &str) -> Document {
+        Document {
+            content: wrap(&format!(
+                "<NetworkLink><name>above_me</name><Link><href>{}</href><refreshMode>onInterval</refreshMode><refreshInterval>{}</refreshInterval></Link></NetworkLink>",
+                escape(href),
+                REFRESH_INTERVAL_SECONDS
+            )),
+            file_name: "above_me_live.kml",
+        }
+    }
+}
+
+impl IntoResponse for Document {
+    fn into_response(self) -> Response {
+        let content_disposition = format!("attachment; filename=\"{}\"", self.file_name);
+
+        (
+            [
+                (
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static(MEDIA_TYPE_KML),
+                ),
+                (
+                    header::CONTENT_DISPOSITION,
+                    HeaderValue::from_str(&content_disposition)
+                        .expect("File name is a valid header value"),
+                ),
+            ],
+            self.content,
+        )
+            .into_response()
+    }
+}
+
+/// Wraps given KML elements into a KML root element
+///
+/// # Arguments
+///
+/// * `content` - The KML elements
+fn wrap(content: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?><kml xmlns=\"http://www.opengis.net/kml/2.2\">{content}</kml>"
+    )
+}
+
+/// Creates the human readable description of a placemark
+///
+/// # Arguments
+///
+/// * `status_dto` - The state of the placemark
+fn create_description(status_dto: &StatusDto) -> String {
+    let mut lines = vec![];
+
+    if let Some(model) = &status_dto.aircraft.model {
+        lines.push(format!("Model: {model}"));
+    }
+
+    if let Some(registration) = &status_dto.aircraft.registration {
+        lines.push(format!("Registration: {registration}"));
+    }
+
+    if let Some(altitude) = status_dto.altitude {
+        lines.push(format!("Altitude: {altitude} m"));
+    }
+
+    if let Some(speed) = status_dto.speed {
+        lines.push(format!("Speed: {speed} km/h"));
+    }
+
+    if let Some(vertical_speed) = status_dto.vertical_speed {
+        lines.push(format!("Vertical speed: {vertical_speed:.1} m/s"));
+    }
+
+    if let Some(course) = status_dto.course {
+        lines.push(format!("Course: {course}°"));
+    }
+
+    if let Some(distance) = status_dto.distance {
+        lines.push(format!("Distance: {distance:.1} km"));
+    }
+
+    if let (Some(bearing), Some(compass_point)) = (status_dto.bearing, status_dto.compass_point) {
+        lines.push(format!("Direction: {compass_point} ({bearing:.0}°)"));
+    }
+
+    /* No `TimeStamp` element, as that would turn on the time slider of Google Earth */
+    let date_time = get_date_time(status_dto.time_stamp);
+    lines.push(format!(
+        "Received: {:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        date_time.year,
+        date_time.month,
+        date_time.day,
+        date_time.hour,
+        date_time.minute,
+        date_time.second
+    ));
+
+    lines.join("\n")
+}
+
+/// Returns the name of the Google Earth icon that represents an aircraft type best
+///
+/// # Arguments
+///
+/// * `aircraft_type` - Type of the aircraft, if known
+fn get_icon_name(aircraft_type: Option<AircraftType>) -> &'static str {
+    match aircraft_type {
+        Some(AircraftType::Helicopter) => "heliport.png",
+        Some(AircraftType::SkyDiver | AircraftType::HangGlider | AircraftType::Paraglider) => {
+            "hiker.png"
+        }
+        Some(AircraftType::Balloon | AircraftType::Blimp) => "placemark_circle.png",
+        Some(AircraftType::Obstacle) => "caution.png",
+        _ => "airports.png",
+    }
+}
+
+/// Escapes special XML characters
+///
+/// # Arguments
+///
+/// * `value` - The value to escape
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(escape("<G&1>"), "&lt;G&amp;1&gt;");
+/// ```
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{aprs::Status, position::Position};
+
+    use super::*;
+
+    #[test]
+    fn renders_states() {
+        let mut status_dtos = [
+            StatusDto::from(
+                &create_status(Some(1000), Some(AircraftType::Glider)),
+                Some(1.5),
+            ),
+            StatusDto::from(&create_status(None, Some(AircraftType::Helicopter)), None),
+        ];
+        status_dtos[0].set_direction(&Position {
+            latitude: 47.0,
+            longitude: 2.0,
+        });
+
+        let result = Document::from_status_dtos(&status_dtos).content;
+
+        assert!(result.starts_with("<?xml"));
+        assert_eq!(result.matches("<Placemark>").count(), 2);
+        assert!(result.contains("<name>&lt;G1&gt;</name>"));
+        assert!(result.contains("<heading>90</heading>"));
+        assert!(result.contains("shapes/airports.png"));
+        assert!(result.contains("shapes/heliport.png"));
+        assert!(result
+            .contains("<altitudeMode>absolute</altitudeMode><coordinates>2,48,1000</coordinates>"));
+        assert!(result.contains(
+            "<altitudeMode>clampToGround</altitudeMode><coordinates>2,48,0</coordinates>"
+        ));
+        assert!(result.contains("Distance: 1.5 km"));
+        assert!(result.contains("Direction: N (0°)"));
+        assert!(result.contains("Received: 1970-01-01 00:00:00 UTC"));
+        assert!(!result.contains("<TimeStamp>"));
+    }
+
+    #[test]
+    fn renders_network_link() {
+        let result = Document::from_network_link("../kml?a=1&b=2").content;
+
+        assert!(result.contains("<href>../kml?a=1&amp;b=2</href>"));
+        assert!(result.contains("<refreshMode>onInterval</refreshMode>"));
+    }
+
+    #[test]
+    fn escape_works() {
+        assert_eq!(escape("<G&1>"), "&lt;G&amp;1&gt;");
+        assert_eq!(escape("D-6507"), "D-6507");
+    }
+
+    fn create_status(altitude: Option<u16>, aircraft_type: Option<AircraftType>) -> Status {
+        let mut status = Status {
+            speed: Some(100),
+            altitude,
+            course: Some(90),
+            aircraft_type,
+            time_stamp: 0,
+            ..Status::create_for_test("AB1234", 48.0, 2.0)
+        };
+        status.aircraft.call_sign = Some(String::from("<G1>"));
+        status.aircraft.registration = Some(String::from("D-6507"));
+        status.aircraft.model = Some(String::from("ASK-21"));
+        status
+    }
+}
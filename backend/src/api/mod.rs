@@ -1,10 +1,23 @@
+pub use routes::aircraft::StatusDto;
 pub use server::init;
-pub use state::App;
+pub use state::{App, Change, Config, Update};
 
 mod routes {
     pub mod aircraft;
+    pub mod area;
+    pub mod events;
+    pub mod kml;
+    pub mod lookup;
     pub mod overview;
+    pub mod track;
+    pub mod websocket;
 }
 
+mod extrapolation;
+mod geojson;
+mod kml;
+mod observer;
 mod server;
 mod state;
+mod store;
+mod subscription;
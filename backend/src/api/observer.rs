@@ -0,0 +1,76 @@
+use crate::position::{calculate_distance, calculate_line_of_sight, Position};
+
+use super::routes::aircraft::StatusDto;
+
+/// Someone looking at the aircraft from a given position and altitude
+pub struct Observer {
+    /// Position of the observer
+    pub position: Position,
+    /// Altitude of the observer in _m_
+    pub altitude: f32,
+}
+
+impl Observer {
+    /// Sets where a status is seen by the observer: slant range, elevation angle and relative
+    /// altitude. Nothing is set if the altitude of the aircraft is unknown.
+    ///
+    /// # Arguments
+    ///
+    /// * `status_dto` - The status to locate
+    ///
+    /// # Examples
+    ///
+    /// * test `observer::locate_works`
+    pub fn locate(&self, mut status_dto: StatusDto) -> StatusDto {
+        if let Some(altitude) = status_dto.altitude {
+            let distance = calculate_distance(&self.position, &status_dto.position);
+            let (slant_range, elevation) =
+                calculate_line_of_sight(distance, self.altitude, f32::from(altitude));
+
+            status_dto.slant_range = Some(slant_range);
+            status_dto.elevation = Some(elevation);
+            status_dto.relative_altitude = Some(f32::from(altitude) - self.altitude);
+        }
+
+        status_dto
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aprs::Status;
+
+    use super::*;
+
+    #[test]
+    fn locate_works() {
+        let sut = Observer {
+            position: Position {
+                latitude: 48.858222,
+                longitude: 2.2945,
+            },
+            altitude: 35.0,
+        };
+
+        let result = sut.locate(StatusDto::from(&create_status(Some(1035)), None));
+
+        assert!((result.slant_range.unwrap() - 3.3182).abs() < 0.001);
+        assert!((result.elevation.unwrap() - 17.53).abs() < 0.01);
+        assert_eq!(result.relative_altitude, Some(1000.0));
+
+        /* Unknown altitude */
+        let result = sut.locate(StatusDto::from(&create_status(None), None));
+
+        assert!(result.slant_range.is_none());
+        assert!(result.elevation.is_none());
+        assert!(result.relative_altitude.is_none());
+    }
+
+    fn create_status(altitude: Option<u16>) -> Status {
+        Status {
+            altitude,
+            time_stamp: 1_700_000_000,
+            ..Status::create_for_test("AB1234", 48.86055, 2.3376)
+        }
+    }
+}
@@ -1,34 +1,98 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
     Json,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    api::App,
-    ogn::{aprs::Status, Aircraft},
-    position::Position,
+    api::{
+        geojson::{FeatureCollection, Format, FormatParameters},
+        observer::Observer,
+        App,
+    },
+    ogn::{aprs::Status, Aircraft, AircraftType},
+    position::{calculate_bearing, get_compass_point, Position},
+    time::get_current_timestamp,
 };
 
-/// Handler for route _/r/:latitude/:longitude/:range_
+/// Handler for route _/r/:latitude/:longitude/:range?extrapolate=:extrapolate&altitude=:altitude&min_elevation=:min_elevation_
 ///
-/// Responds with a list of aircraft in the _:range_ around _:latitude_ and _:longitude_
+/// Responds with a list of aircraft in the _:range_ around _:latitude_ and _:longitude_,
+/// either as JSON or as GeoJSON `FeatureCollection` (see `Format::negotiate`). If
+/// _:extrapolate_ is true, the states are projected forward to the current time.
+///
+/// If _:altitude_ of the caller is given, every status also contains slant range, elevation
+/// angle and relative altitude as seen from there. With _:min_elevation_, only aircraft that are
+/// at least that many degrees above the horizon are returned.
 pub async fn handler(
     Path((latitude, longitude, range)): Path<(f32, f32, f32)>,
+    Query(parameters): Query<Parameters>,
+    Query(format_parameters): Query<FormatParameters>,
     State(app): State<App>,
-) -> Json<Response> {
+    headers: HeaderMap,
+) -> impl IntoResponse {
     /* Ensure range can be used as f32 */
     let position = Position {
         latitude,
         longitude,
     };
 
-    Json(Response {
-        latitude,
-        longitude,
-        range,
-        states: app.get_filtered_status_dtos(&position, range),
-    })
+    if parameters.min_elevation.is_some() && parameters.altitude.is_none() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json("Minimum elevation requires an altitude"),
+        )
+            .into_response();
+    }
+
+    let mut states = if parameters.extrapolate {
+        app.get_extrapolated_status_dtos(&position, range)
+    } else {
+        app.get_filtered_status_dtos(&position, range)
+    };
+
+    if let Some(altitude) = parameters.altitude {
+        let observer = Observer {
+            position: position.clone(),
+            altitude,
+        };
+
+        states = states
+            .into_iter()
+            .map(|status_dto| observer.locate(status_dto))
+            .filter(|status_dto| match parameters.min_elevation {
+                /* Aircraft with unknown altitude can't be checked */
+                Some(min_elevation) => status_dto
+                    .elevation
+                    .is_some_and(|elevation| elevation >= min_elevation),
+                None => true,
+            })
+            .collect();
+    }
+
+    match Format::negotiate(&format_parameters, &headers) {
+        Format::GeoJson => FeatureCollection::from_status_dtos(&states).into_response(),
+        Format::Json => Json(Response {
+            latitude,
+            longitude,
+            range,
+            states,
+        })
+        .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Parameters {
+    /// Whether the states should be extrapolated to the current time
+    #[serde(default)]
+    extrapolate: bool,
+    /// Altitude of the caller in _m_, to locate the aircraft from
+    altitude: Option<f32>,
+    /// Minimum angle above the horizon in degrees, requires `altitude`
+    min_elevation: Option<f32>,
 }
 
 #[derive(Serialize)]
@@ -44,7 +108,7 @@ pub struct Response {
 }
 
 /// Dto representation of an aircraft status, containing the distance to the
-/// requested postion in km.
+/// requested postion in km, if a position was requested.
 #[derive(Clone, Serialize)]
 pub struct StatusDto {
     /// Affected aircraft
@@ -61,14 +125,38 @@ pub struct StatusDto {
     pub turn_rate: Option<f32>,
     /// Course of aircraft
     pub course: Option<u16>,
+    /// Generic type of aircraft
+    #[serde(skip_serializing)]
+    pub aircraft_type: Option<AircraftType>,
     /// Timestamp of receiving status
     pub time_stamp: u64,
+    /// Seconds since receiving status
+    pub age: u64,
     /// Distance to given postion in km
-    pub distance: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub distance: Option<f32>,
+    /// Bearing from given postion in degrees, clockwise from north
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bearing: Option<f32>,
+    /// Compass point of `bearing`, e.g. _NNE_
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compass_point: Option<&'static str>,
+    /// Direct distance to given observer in km, through the air
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slant_range: Option<f32>,
+    /// Angle above the horizon of given observer in degrees
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elevation: Option<f32>,
+    /// Altitude above given observer in _m_, negative if below
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub relative_altitude: Option<f32>,
+    /// Seconds that the status has been extrapolated by, if it is estimated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extrapolation_age: Option<u64>,
 }
 
 impl StatusDto {
-    pub fn from(status: &Status, distance: f32) -> Self {
+    pub fn from(status: &Status, distance: Option<f32>) -> Self {
         Self {
             aircraft: status.aircraft.clone(),
             position: status.position.clone(),
@@ -77,8 +165,28 @@ impl StatusDto {
             altitude: status.altitude,
             turn_rate: status.turn_rate,
             course: status.course,
+            aircraft_type: status.aircraft_type,
             time_stamp: status.time_stamp,
+            age: get_current_timestamp().saturating_sub(status.time_stamp),
             distance,
+            bearing: None,
+            compass_point: None,
+            slant_range: None,
+            elevation: None,
+            relative_altitude: None,
+            extrapolation_age: None,
         }
     }
+
+    /// Sets bearing and compass point of the status as seen from a position
+    ///
+    /// # Arguments
+    ///
+    /// * `origin` - The position to look from
+    pub fn set_direction(&mut self, origin: &Position) {
+        let bearing = calculate_bearing(origin, &self.position);
+
+        self.bearing = Some(bearing);
+        self.compass_point = Some(get_compass_point(bearing));
+    }
 }
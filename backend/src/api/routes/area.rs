@@ -0,0 +1,111 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use crate::{
+    api::{
+        geojson::{Area, FeatureCollection, Format, FormatParameters},
+        App,
+    },
+    position::BoundingBox,
+};
+
+use super::aircraft::StatusDto;
+
+/// Handler for route _/bbox/:south/:west/:north/:east_
+///
+/// Responds with a list of aircraft within the bounding box, either as JSON or as GeoJSON
+/// `FeatureCollection` (see `Format::negotiate`). If _:west_ is greater than _:east_, the
+/// bounding box crosses the antimeridian. Responds with 400 if the bounding box is invalid.
+pub async fn bounding_box_handler(
+    Path((south, west, north, east)): Path<(f32, f32, f32, f32)>,
+    Query(format_parameters): Query<FormatParameters>,
+    State(app): State<App>,
+    headers: HeaderMap,
+) -> Response {
+    let is_latitude = |value: f32| (-90.0..=90.0).contains(&value);
+    let is_longitude = |value: f32| (-180.0..=180.0).contains(&value);
+
+    if !is_latitude(south) || !is_latitude(north) || !is_longitude(west) || !is_longitude(east) {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json("Bounding box is out of range"),
+        )
+            .into_response();
+    }
+
+    if south > north {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json("South must not be greater than north"),
+        )
+            .into_response();
+    }
+
+    let states = app.get_status_dtos_in_bounding_box(&BoundingBox {
+        south,
+        west,
+        north,
+        east,
+    });
+
+    match Format::negotiate(&format_parameters, &headers) {
+        Format::GeoJson => FeatureCollection::from_status_dtos(&states).into_response(),
+        Format::Json => Json(BoundingBoxResponse {
+            south,
+            west,
+            north,
+            east,
+            states,
+        })
+        .into_response(),
+    }
+}
+
+/// Handler for route _/polygon_
+///
+/// Expects a GeoJSON _Polygon_ or _MultiPolygon_ (or a _Feature_ of it) as body and responds
+/// with a list of aircraft within it, either as JSON or as GeoJSON `FeatureCollection` (see
+/// `Format::negotiate`). Responds with 400 if the polygon is invalid.
+pub async fn polygon_handler(
+    Query(format_parameters): Query<FormatParameters>,
+    State(app): State<App>,
+    headers: HeaderMap,
+    Json(area): Json<Area>,
+) -> Response {
+    let polygons = match area.into_polygons() {
+        Ok(polygons) => polygons,
+        Err(message) => return (StatusCode::BAD_REQUEST, Json(message)).into_response(),
+    };
+
+    let states = app.get_status_dtos_in_polygons(&polygons);
+
+    match Format::negotiate(&format_parameters, &headers) {
+        Format::GeoJson => FeatureCollection::from_status_dtos(&states).into_response(),
+        Format::Json => Json(PolygonResponse { states }).into_response(),
+    }
+}
+
+#[derive(Serialize)]
+pub struct BoundingBoxResponse {
+    /// Equals given south parameter
+    south: f32,
+    /// Equals given west parameter
+    west: f32,
+    /// Equals given north parameter
+    north: f32,
+    /// Equals given east parameter
+    east: f32,
+    /// The aircraft states within the bounding box
+    states: Vec<StatusDto>,
+}
+
+#[derive(Serialize)]
+pub struct PolygonResponse {
+    /// The aircraft states within the polygon
+    states: Vec<StatusDto>,
+}
@@ -0,0 +1,234 @@
+use std::convert::Infallible;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, HeaderName, HeaderValue},
+    response::{
+        sse::{self, KeepAlive, Sse},
+        IntoResponse,
+    },
+};
+use futures_util::{stream, Stream, StreamExt};
+
+use crate::{
+    api::{
+        subscription::{Event, Subscription},
+        App,
+    },
+    position::Position,
+};
+
+/// Header that is sent by clients when reconnecting, containing the id of the last received event
+const HEADER_LAST_EVENT_ID: &str = "last-event-id";
+/// Header that tells nginx to not buffer the response, so that events are passed through directly
+const HEADER_ACCEL_BUFFERING: &str = "x-accel-buffering";
+/// Name of the event that tells clients to discard all aircraft, as the changes they missed are
+/// not known anymore
+const EVENT_RESET: &str = "reset";
+
+/// Handler for route _/r/:latitude/:longitude/:range/events_
+///
+/// Responds with a stream of server-sent events for every aircraft that enters, updates in or
+/// leaves the _:range_ around _:latitude_ and _:longitude_. Initially, an "entered" event is
+/// sent for every aircraft that is currently in range. If the client sends a _Last-Event-ID_
+/// header, only the changes since that event are sent initially. If those changes are not known
+/// anymore, a "reset" event is sent before the current states instead.
+pub async fn handler(
+    Path((latitude, longitude, range)): Path<(f32, f32, f32)>,
+    State(app): State<App>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let position = Position {
+        latitude,
+        longitude,
+    };
+
+    let last_event_id = headers
+        .get(HEADER_LAST_EVENT_ID)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok());
+
+    let resumed = last_event_id
+        .and_then(|sequence| Subscription::resume(&app, position.clone(), range, sequence));
+
+    let (subscription, events, is_reset) = match resumed {
+        Some((subscription, events)) => (subscription, events, false),
+        None => {
+            let (subscription, events) = Subscription::create(&app, position, range);
+            (subscription, events, last_event_id.is_some())
+        }
+    };
+
+    (
+        [(
+            HeaderName::from_static(HEADER_ACCEL_BUFFERING),
+            HeaderValue::from_static("no"),
+        )],
+        Sse::new(create_stream(subscription, events, is_reset)).keep_alive(KeepAlive::default()),
+    )
+}
+
+/// Creates a stream of server-sent events, starting with the initial `events`
+///
+/// Events are sent in batches that belong to a single sequence number, e.g. the initial events
+/// or the events after catching up. Only the last event of a batch gets the sequence number as
+/// id, so that a client which disconnects in the middle of a batch resumes before it.
+///
+/// # Arguments
+///
+/// * `subscription` - The `Subscription` to receive further events from
+/// * `events` - Events that should be sent first
+/// * `is_reset` - Whether the client has to discard all aircraft before the initial `events`
+///
+/// # Examples
+///
+/// * test `events::resume_works_after_partial_batch`
+fn create_stream(
+    subscription: Subscription,
+    events: Vec<Event>,
+    is_reset: bool,
+) -> impl Stream<Item = Result<sse::Event, Infallible>> {
+    /* Browsers don't dispatch events without data */
+    let reset_event = is_reset.then(|| {
+        sse::Event::default()
+            .event(EVENT_RESET)
+            .data(format!("{{\"type\":\"{EVENT_RESET}\"}}"))
+    });
+
+    let mut initial_events = reset_event
+        .into_iter()
+        .chain(events.iter().map(to_sse_event))
+        .collect::<Vec<sse::Event>>();
+
+    if let Some(last_event) = initial_events.pop() {
+        initial_events.push(last_event.id(subscription.get_sequence().to_string()));
+    }
+
+    let further_events = stream::unfold(subscription, |mut subscription| async move {
+        let event = subscription.next().await?;
+        let mut sse_event = to_sse_event(&event);
+
+        if !subscription.has_pending_events() {
+            sse_event = sse_event.id(subscription.get_sequence().to_string());
+        }
+
+        Some((Ok(sse_event), subscription))
+    });
+
+    stream::iter(initial_events.into_iter().map(Ok)).chain(further_events)
+}
+
+/// Converts an `Event` into a server-sent event without id
+///
+/// # Arguments
+///
+/// * `event` - The `Event` to convert
+fn to_sse_event(event: &Event) -> sse::Event {
+    sse::Event::default()
+        .event(event.get_name())
+        .json_data(event)
+        .expect("Event can always be serialized")
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body;
+
+    use crate::{
+        api::{state::UPDATE_CHANNEL_CAPACITY, Config},
+        aprs::Status,
+        ogn::ddb,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn resume_works_after_partial_batch() {
+        let app = App::create(Config::default(), ddb::Index::default());
+
+        app.push_status(Status::create_for_test("AB1234", 48.858222, 2.2945));
+        let sequence = app.get_last_update_sequence();
+
+        /* Missed while the client was disconnected */
+        app.push_status(Status::create_for_test("AB1234", 48.86055, 2.3376));
+        app.push_status(Status::create_for_test("CD5678", 48.858222, 2.2945));
+        app.push_status(Status::create_for_test("EF9012", 48.858222, 2.2945));
+
+        let (subscription, events) =
+            Subscription::resume(&app, create_position(), 4.0, sequence).unwrap();
+        let sse_events = render(create_stream(subscription, events, false), 3).await;
+
+        assert_eq!(get_ids(&sse_events), vec![None, None, Some(4)]);
+
+        /* Client disconnects after the first event of the batch, so it keeps the id it received
+         * before and gets the whole batch again */
+        let last_event_id = get_ids(&sse_events[..1])
+            .into_iter()
+            .flatten()
+            .last()
+            .unwrap_or(sequence);
+        let (_, events) =
+            Subscription::resume(&app, create_position(), 4.0, last_event_id).unwrap();
+
+        assert_eq!(events.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn stream_sets_id_after_catching_up() {
+        let app = App::create(Config::default(), ddb::Index::default());
+
+        app.push_status(Status::create_for_test("AB1234", 48.858222, 2.2945));
+
+        let (subscription, events) = Subscription::create(&app, create_position(), 4.0);
+
+        app.push_status(Status::create_for_test("CD5678", 48.858222, 2.2945));
+
+        /* Subscriber is too slow, so it has to catch up with both aircraft */
+        for _ in 0..UPDATE_CHANNEL_CAPACITY {
+            app.push_status(Status::create_for_test("GH3456", 50.0, 10.0));
+        }
+
+        let sse_events = render(create_stream(subscription, events, true), 4).await;
+        let last_sequence = app.get_last_update_sequence();
+
+        assert!(sse_events[0].contains("event: reset"));
+        assert_eq!(
+            get_ids(&sse_events),
+            vec![None, Some(1), None, Some(last_sequence)]
+        );
+    }
+
+    fn create_position() -> Position {
+        Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        }
+    }
+
+    /// Renders the first `count` events of a stream as they are sent to the client
+    async fn render(
+        stream: impl Stream<Item = Result<sse::Event, Infallible>> + Send + 'static,
+        count: usize,
+    ) -> Vec<String> {
+        let body = Sse::new(stream.take(count)).into_response().into_body();
+        let bytes = body::to_bytes(body, usize::MAX).await.unwrap();
+
+        String::from_utf8(bytes.to_vec())
+            .unwrap()
+            .split_terminator("\n\n")
+            .map(String::from)
+            .collect()
+    }
+
+    fn get_ids(sse_events: &[String]) -> Vec<Option<u64>> {
+        sse_events
+            .iter()
+            .map(|sse_event| {
+                sse_event
+                    .lines()
+                    .find_map(|line| line.strip_prefix("id: "))
+                    .map(|id| id.parse().unwrap())
+            })
+            .collect()
+    }
+}
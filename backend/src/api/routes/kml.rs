@@ -0,0 +1,58 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::{
+    api::{kml::Document, App},
+    position::Position,
+};
+
+/// Header that is set by reverse proxies, containing the protocol used by the client
+const HEADER_FORWARDED_PROTO: &str = "x-forwarded-proto";
+
+/// Handler for route _/r/:latitude/:longitude/:range/kml_
+///
+/// Responds with a KML document that contains a placemark for every aircraft in the _:range_
+/// around _:latitude_ and _:longitude_, e.g. to open it in Google Earth.
+pub async fn handler(
+    Path((latitude, longitude, range)): Path<(f32, f32, f32)>,
+    State(app): State<App>,
+) -> impl IntoResponse {
+    let position = Position {
+        latitude,
+        longitude,
+    };
+
+    Document::from_status_dtos(&app.get_filtered_status_dtos(&position, range))
+}
+
+/// Handler for route _/r/:latitude/:longitude/:range/kml/live_
+///
+/// Responds with a KML document that contains a network link to
+/// _/r/:latitude/:longitude/:range/kml_, so that Google Earth refreshes the aircraft
+/// automatically. As the document is usually saved before it is opened, the link is absolute and
+/// built from the _Host_ header of the request.
+pub async fn live_handler(
+    Path((latitude, longitude, range)): Path<(f32, f32, f32)>,
+    headers: HeaderMap,
+) -> Response {
+    let Some(host) = headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return (StatusCode::BAD_REQUEST, Json("Host header is missing")).into_response();
+    };
+
+    let protocol = headers
+        .get(HEADER_FORWARDED_PROTO)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("http");
+
+    Document::from_network_link(&format!(
+        "{protocol}://{host}/r/{latitude}/{longitude}/{range}/kml"
+    ))
+    .into_response()
+}
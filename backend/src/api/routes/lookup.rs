@@ -0,0 +1,79 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    api::App,
+    ogn::{Aircraft, AircraftId},
+};
+
+use super::aircraft::StatusDto;
+
+/// Handler for route _/aircraft/:id_
+///
+/// Responds with the current status of the aircraft with given _:id_ or 404, if there is no
+/// current status of that aircraft.
+pub async fn handler(Path(id): Path<String>, State(app): State<App>) -> Response {
+    /* Aircraft ids are hex values that are stored in upper case */
+    match app.get_status_dto(&id.to_uppercase()) {
+        Some(status_dto) => Json(status_dto).into_response(),
+        None => (StatusCode::NOT_FOUND, Json("Aircraft not found")).into_response(),
+    }
+}
+
+/// Handler for route _/aircraft?registration=:registration&call_sign=:call_sign_
+///
+/// Responds with all known aircraft that match given _:registration_ and / or _:call_sign_,
+/// each with its current status if there is one. Responds with 404 if no aircraft matches.
+pub async fn search_handler(
+    Query(parameters): Query<SearchParameters>,
+    State(app): State<App>,
+) -> Response {
+    if parameters.registration.is_none() && parameters.call_sign.is_none() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json("Either registration or call sign must be given"),
+        )
+            .into_response();
+    }
+
+    let aircraft = app.find_aircraft_dtos(
+        parameters.registration.as_deref(),
+        parameters.call_sign.as_deref(),
+    );
+
+    if aircraft.is_empty() {
+        return (StatusCode::NOT_FOUND, Json("Aircraft not found")).into_response();
+    }
+
+    Json(SearchResponse { aircraft }).into_response()
+}
+
+#[derive(Deserialize)]
+pub struct SearchParameters {
+    /// Registration to search for
+    registration: Option<String>,
+    /// Call sign to search for
+    call_sign: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SearchResponse {
+    /// The aircraft that match the given parameters
+    aircraft: Vec<AircraftDto>,
+}
+
+/// Dto representation of a known aircraft with its current status
+#[derive(Serialize)]
+pub struct AircraftDto {
+    /// Id of the aircraft
+    pub id: AircraftId,
+    /// The aircraft
+    pub aircraft: Aircraft,
+    /// Current status of the aircraft, `None` if it is currently not seen
+    pub state: Option<StatusDto>,
+}
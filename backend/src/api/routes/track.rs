@@ -0,0 +1,41 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use crate::api::{
+    geojson::{FeatureCollection, Format, FormatParameters},
+    state::TrackPoint,
+    App,
+};
+
+/// Handler for route _/aircraft/:id/track_
+///
+/// Responds with the recent positions of the aircraft with given _:id_, either as JSON or as
+/// GeoJSON `FeatureCollection` (see `Format::negotiate`), or 404, if there is no current
+/// status of that aircraft.
+pub async fn handler(
+    Path(id): Path<String>,
+    Query(format_parameters): Query<FormatParameters>,
+    State(app): State<App>,
+    headers: HeaderMap,
+) -> Response {
+    /* Aircraft ids are hex values that are stored in upper case */
+    let Some(points) = app.get_track(&id.to_uppercase()) else {
+        return (StatusCode::NOT_FOUND, Json("Aircraft not found")).into_response();
+    };
+
+    match Format::negotiate(&format_parameters, &headers) {
+        Format::GeoJson => FeatureCollection::from_track(&points).into_response(),
+        Format::Json => Json(TrackResponse { points }).into_response(),
+    }
+}
+
+#[derive(Serialize)]
+pub struct TrackResponse {
+    /// Recent positions of the aircraft, oldest first
+    points: Vec<TrackPoint>,
+}
@@ -0,0 +1,88 @@
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        Path, State, WebSocketUpgrade,
+    },
+    response::Response,
+};
+use log::debug;
+use tokio::select;
+
+use crate::{
+    api::{
+        subscription::{Event, Subscription},
+        App,
+    },
+    position::Position,
+};
+
+/// Handler for route _/r/:latitude/:longitude/:range/ws_
+///
+/// Upgrades to a WebSocket connection that sends a message for every aircraft that enters,
+/// updates in or leaves the _:range_ around _:latitude_ and _:longitude_. Initially, an
+/// "entered" message is sent for every aircraft that is currently in range. Clients that are too
+/// slow and miss changes are caught up with the current states.
+pub async fn handler(
+    Path((latitude, longitude, range)): Path<(f32, f32, f32)>,
+    State(app): State<App>,
+    web_socket_upgrade: WebSocketUpgrade,
+) -> Response {
+    let position = Position {
+        latitude,
+        longitude,
+    };
+
+    web_socket_upgrade.on_upgrade(move |web_socket| handle(web_socket, app, position, range))
+}
+
+/// Sends events of a `Subscription` to a WebSocket until either side closes
+///
+/// # Arguments
+///
+/// * `web_socket` - The upgraded WebSocket connection
+/// * `app` - The `App` to subscribe to
+/// * `position` - Center of the watched area
+/// * `range` - Range around given `position` that should be watched
+async fn handle(mut web_socket: WebSocket, app: App, position: Position, range: f32) {
+    let (mut subscription, events) = Subscription::create(&app, position, range);
+
+    for event in &events {
+        if send(&mut web_socket, event).await.is_err() {
+            return;
+        }
+    }
+
+    loop {
+        select! {
+            event = subscription.next() => {
+                let Some(event) = event else {
+                    break;
+                };
+
+                if send(&mut web_socket, &event).await.is_err() {
+                    break;
+                }
+            },
+            message = web_socket.recv() => {
+                match message {
+                    Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                    /* Other messages from the client are irrelevant */
+                    Some(Ok(_)) => continue,
+                }
+            }
+        }
+    }
+
+    debug!("WebSocket connection closed");
+}
+
+/// Sends an `Event` as JSON text message
+///
+/// # Arguments
+///
+/// * `web_socket` - The WebSocket to send to
+/// * `event` - The `Event` to send
+async fn send(web_socket: &mut WebSocket, event: &Event) -> Result<(), axum::Error> {
+    let json = serde_json::to_string(event).map_err(axum::Error::new)?;
+    web_socket.send(Message::Text(json.into())).await
+}
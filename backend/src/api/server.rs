@@ -1,10 +1,13 @@
-use axum::{routing::get, Router};
+use axum::{
+    routing::{get, post},
+    Router,
+};
 use laika::shotgun;
 use log::info;
 use std::io::Error;
 use tokio::{net::TcpListener, net::ToSocketAddrs};
 
-use super::routes::{aircraft, overview};
+use super::routes::{aircraft, area, events, kml, lookup, overview, track, websocket};
 use super::state::App;
 
 /// Initializes a tcp server that serves our API
@@ -14,7 +17,7 @@ use super::state::App;
 /// * `address` - The address that the server will bind to
 /// * `app` - The `App` that the API will use for its data
 /// * `shutdown_rx` - A shotgun `Receiver<()>` that will shut down the server gracefully when a
-///    message is received.
+///   message is received.
 ///
 /// # Returns
 ///
@@ -23,12 +26,12 @@ use super::state::App;
 /// # Examples
 ///
 /// ```
-/// use api::App;
+/// use api::{App, Config};
 /// use tokio::{spawn, sync::oneshot};
 ///
 /// let address = "127.0.0.1:8080";
 /// let (shutdown_tx, shutdown_rx) = oneshot::channel();
-/// let app = App::create();
+/// let app = App::create(Config::default(), ddb::Index::default());
 ///
 /// spawn(async move {
 ///     api::init(&address, app, shutdown_rx)
@@ -46,6 +49,27 @@ pub async fn init<A: ToSocketAddrs>(
 ) -> Result<(), Error> {
     let app = Router::new()
         .route("/r/{latitude}/{longitude}/{range}", get(aircraft::handler))
+        .route(
+            "/r/{latitude}/{longitude}/{range}/ws",
+            get(websocket::handler),
+        )
+        .route(
+            "/r/{latitude}/{longitude}/{range}/events",
+            get(events::handler),
+        )
+        .route("/r/{latitude}/{longitude}/{range}/kml", get(kml::handler))
+        .route(
+            "/r/{latitude}/{longitude}/{range}/kml/live",
+            get(kml::live_handler),
+        )
+        .route(
+            "/bbox/{south}/{west}/{north}/{east}",
+            get(area::bounding_box_handler),
+        )
+        .route("/polygon", post(area::polygon_handler))
+        .route("/aircraft", get(lookup::search_handler))
+        .route("/aircraft/{id}", get(lookup::handler))
+        .route("/aircraft/{id}/track", get(track::handler))
         .route("/status", get(overview::handler))
         .with_state(app);
 
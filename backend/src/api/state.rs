@@ -1,30 +1,133 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet, VecDeque},
     sync::{
         atomic::{AtomicU64, Ordering},
-        Arc, Mutex, MutexGuard,
+        Arc, Mutex,
     },
 };
 
-use serde::Serialize;
+use arc_swap::ArcSwap;
+use im::Vector;
+use serde::{Deserialize, Serialize};
+use tokio::{
+    sync::broadcast,
+    time::{interval, Duration, MissedTickBehavior},
+};
 
 use crate::{
     aprs::Status,
-    position::{calculate_distance, Position},
+    ogn::{ddb, AircraftId, AircraftType},
+    position::{calculate_distance, BoundingBox, Polygon, Position},
     time::get_current_timestamp,
 };
 
-use super::routes::aircraft::StatusDto;
+use super::{
+    extrapolation::{self, extrapolate},
+    routes::{aircraft::StatusDto, lookup::AircraftDto},
+    store::Store,
+};
+
+/// Default for `Config::max_age`
+const DEFAULT_MAX_AGE: u64 = 60 * 5; /* 5 minutes */
+/// Number of updates that are buffered for subscribers. Subscribers that fall behind further
+/// will miss updates. Also the number of recent updates that are kept for resuming.
+pub const UPDATE_CHANNEL_CAPACITY: usize = 1024;
+/// Default for `Config::track_max_points`
+const DEFAULT_TRACK_MAX_POINTS: usize = 200;
+/// Default for `Config::expiry_interval`
+const DEFAULT_EXPIRY_INTERVAL: u64 = 10;
 
-const MAX_AGE_DIFF: u64 = 60 * 5; /* 5 minutes */
+/// Configuration of the stored states
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Maximum number of positions that are kept per aircraft track
+    pub track_max_points: usize,
+    /// Maximum age of positions that are kept per aircraft track (in seconds). If not given, the
+    /// maximum age of the state of the aircraft applies, so that tracks contain no older data.
+    pub track_max_age: Option<u64>,
+    /// Interval of removing outdated states (in seconds)
+    pub expiry_interval: u64,
+    /// Maximum age of states (in seconds), after which they are outdated
+    pub max_age: u64,
+    /// Maximum age of states per aircraft type (in seconds), overriding `max_age`
+    pub max_age_by_type: HashMap<AircraftType, u64>,
+}
 
 /// Our shared application state for the API
+///
+/// Readers work on the latest `Snapshot` without taking any lock, so that they never block
+/// the ingest of new states. Writers clone the latest `Snapshot` (which is cheap as it is built
+/// on persistent collections), change it and swap it in.
 #[derive(Clone)]
 pub struct App {
-    /// Reference to all currently stored states
-    states: Arc<Mutex<HashMap<String, Status>>>,
+    /// Latest snapshot of the stored states
+    snapshot: Arc<ArcSwap<Snapshot>>,
+    /// Lock that writers of the snapshot hold, so that no changes get lost
+    write_lock: Arc<Mutex<()>>,
     /// Timestamp of last APRS line received
     last_aprs_update: Arc<AtomicU64>,
+    /// Address of the APRS server that is currently connected
+    aprs_server: Arc<Mutex<Option<String>>>,
+    /// Number of times that the APRS connection has been dropped as the server stopped
+    /// responding, e.g. it was stale or connecting timed out
+    aprs_stalls: Arc<AtomicU64>,
+    /// Timestamp of the latest stale or timed out APRS connection
+    last_aprs_stall: Arc<AtomicU64>,
+    /// Sender for changes of the stored states
+    update_tx: broadcast::Sender<Update>,
+    /// Sequence number of the latest update
+    last_update_sequence: Arc<AtomicU64>,
+    /// The most recent updates, oldest first
+    recent_updates: Arc<Mutex<VecDeque<Update>>>,
+    /// Index of known DDB aircraft
+    aircraft_index: Arc<ddb::Index>,
+    /// Configuration of the stored states
+    config: Arc<Config>,
+}
+
+/// Immutable state of the stored states at some point in time
+#[derive(Clone, Default)]
+struct Snapshot {
+    /// All stored states
+    states: Store,
+    /// Recent positions of all aircraft with a stored state, oldest first
+    tracks: im::HashMap<AircraftId, Vector<TrackPoint>>,
+}
+
+/// A change of the stored states
+#[derive(Clone)]
+pub struct Update {
+    /// Sequence number of the update, increasing by one with every update
+    pub sequence: u64,
+    /// The actual change
+    pub change: Change,
+}
+
+/// Kind of change of the stored states
+#[derive(Clone)]
+pub enum Change {
+    /// Status of an aircraft has been stored / updated
+    Stored(Status),
+    /// Aircraft has been lost as its status is outdated. Contains the last status.
+    Lost(Status),
+}
+
+/// DTO for a single position of an aircraft track
+#[derive(Clone, Serialize)]
+pub struct TrackPoint {
+    /// Position of aircraft
+    pub position: Position,
+    /// Altitude in _m_
+    pub altitude: Option<u16>,
+    /// Speed in _km/h_
+    pub speed: Option<u16>,
+    /// Vertical speed in _m/s_
+    pub vertical_speed: Option<f32>,
+    /// Course of aircraft
+    pub course: Option<u16>,
+    /// Timestamp of receiving status
+    pub time_stamp: u64,
 }
 
 /// DTO for status overview
@@ -36,23 +139,106 @@ pub struct Overview {
     pub last_status_update: Option<u64>,
     /// Timestamp of last APRS update received
     pub last_aprs_update: Option<u64>,
+    /// Address of the APRS server that is currently connected, if any
+    pub aprs_server: Option<String>,
+    /// Number of times that the APRS connection has been dropped as the server stopped
+    /// responding, e.g. nothing was received or connecting timed out
+    pub aprs_stalls: u64,
+    /// Timestamp of the latest stale or timed out APRS connection, if any
+    pub last_aprs_stall: Option<u64>,
 }
 
 impl App {
     /// Creates a new `App`
     ///
+    /// # Arguments
+    ///
+    /// * `config` - Configuration of the stored states
+    /// * `aircraft_index` - Index of known DDB aircraft
+    ///
     /// # Examples
     ///
     /// ```
-    /// use api::App;
+    /// use api::{App, Config};
     ///
-    /// let app = App::create();
+    /// let aircraft = Arc::new(fetch_aircraft(url).await.unwrap());
+    /// let app = App::create(Config::default(), ddb::Index::create(aircraft));
     /// ```
-    pub fn create() -> App {
+    pub fn create(config: Config, aircraft_index: ddb::Index) -> App {
+        let (update_tx, _) = broadcast::channel(UPDATE_CHANNEL_CAPACITY);
+
         App {
-            states: Arc::new(Mutex::new(HashMap::new())),
+            snapshot: Arc::new(ArcSwap::from_pointee(Snapshot::default())),
+            write_lock: Arc::new(Mutex::new(())),
             last_aprs_update: Arc::new(AtomicU64::new(0)),
+            aprs_server: Arc::new(Mutex::new(None)),
+            aprs_stalls: Arc::new(AtomicU64::new(0)),
+            last_aprs_stall: Arc::new(AtomicU64::new(0)),
+            update_tx,
+            last_update_sequence: Arc::new(AtomicU64::new(0)),
+            recent_updates: Arc::new(Mutex::new(VecDeque::with_capacity(UPDATE_CHANNEL_CAPACITY))),
+            aircraft_index: Arc::new(aircraft_index),
+            config: Arc::new(config),
+        }
+    }
+
+    /// Subscribes to changes of the stored states
+    ///
+    /// # Returns
+    ///
+    /// A `Receiver` that receives an `Update` whenever a status is stored or removed
+    ///
+    /// # Examples
+    ///
+    /// * test `state::subscribe_receives_updates`
+    pub fn subscribe(&self) -> broadcast::Receiver<Update> {
+        self.update_tx.subscribe()
+    }
+
+    /// Returns the sequence number of the latest update, 0 if there was none yet
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_updates_since_works`
+    pub fn get_last_update_sequence(&self) -> u64 {
+        self.last_update_sequence.load(Ordering::SeqCst)
+    }
+
+    /// Returns all updates that happened after the update with given sequence number
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - Sequence number of the last known update
+    ///
+    /// # Returns
+    ///
+    /// The updates after `sequence`, oldest first, or `None` if some of those updates are not
+    /// kept anymore.
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_updates_since_works`
+    pub fn get_updates_since(&self, sequence: u64) -> Option<Vec<Update>> {
+        let recent_updates = self.recent_updates.lock().expect("Mutex was poisoned");
+
+        if sequence > self.get_last_update_sequence() {
+            /* Sequence is from the future, e.g. from before a restart */
+            return None;
+        }
+
+        if let Some(oldest) = recent_updates.front() {
+            if oldest.sequence > sequence + 1 {
+                return None;
+            }
         }
+
+        Some(
+            recent_updates
+                .iter()
+                .filter(|update| update.sequence > sequence)
+                .cloned()
+                .collect(),
+        )
     }
 
     /// Returns the states in the `App` that match given filters as dtos.
@@ -72,27 +258,248 @@ impl App {
     /// * test `state::get_filtered_states_checks_range`
     /// * test `state::get_filtered_states_orders_correctly`
     pub fn get_filtered_status_dtos(&self, position: &Position, range: f32) -> Vec<StatusDto> {
-        let mut states = self.states.lock().expect("Mutex was poisoned");
-
-        App::remove_outdated_states(&mut states);
+        let snapshot = self.snapshot.load();
+        let current_timestamp = get_current_timestamp();
 
-        let mut status_dtos = states
-            .values()
+        let mut status_dtos = snapshot
+            .states
+            .get_nearby(&BoundingBox::around(position, range))
+            .into_iter()
+            .filter(|status| self.is_current(status, current_timestamp))
             .map(|status| (status, calculate_distance(position, &status.position)))
             .filter(|&(_, distance)| distance <= range)
-            .map(|(status, distance)| StatusDto::from(status, distance))
+            .map(|(status, distance)| {
+                let mut status_dto = StatusDto::from(status, Some(distance));
+                status_dto.set_direction(position);
+                status_dto
+            })
             .collect::<Vec<StatusDto>>();
 
-        status_dtos.sort_unstable_by(|status_dto_1, status_dto_2| {
-            status_dto_1
-                .distance
-                .partial_cmp(&status_dto_2.distance)
-                .unwrap()
-        });
+        sort_by_distance(&mut status_dtos);
+        status_dtos
+    }
+
+    /// Returns the states in the `App` that match given filters as dtos, extrapolated to the
+    /// current time (see `extrapolation::extrapolate`).
+    ///
+    /// # Arguments
+    /// * `position` - The position that should be searched for
+    /// * `range` - Range around given `position` that should be searched for.
+    ///
+    /// # Returns
+    ///
+    /// Returns dtos of the states whose extrapolated positions are within `range` around given
+    /// `position`, sorted in ascending oder by distance to `position`.
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_extrapolated_status_dtos_works`
+    pub fn get_extrapolated_status_dtos(&self, position: &Position, range: f32) -> Vec<StatusDto> {
+        let snapshot = self.snapshot.load();
+        let current_timestamp = get_current_timestamp();
+
+        /* Aircraft may have been extrapolated into range from outside */
+        let max_distance =
+            extrapolation::MAX_SPEED * self.config.get_max_age_limit() as f32 / 3600.0;
 
+        let mut status_dtos = snapshot
+            .states
+            .get_nearby(&BoundingBox::around(position, range + max_distance))
+            .into_iter()
+            .filter(|status| self.is_current(status, current_timestamp))
+            .map(|status| extrapolate(StatusDto::from(status, None), current_timestamp))
+            .filter_map(|mut status_dto| {
+                let distance = calculate_distance(position, &status_dto.position);
+                status_dto.distance = Some(distance);
+                status_dto.set_direction(position);
+
+                (distance <= range).then_some(status_dto)
+            })
+            .collect::<Vec<StatusDto>>();
+
+        sort_by_distance(&mut status_dtos);
         status_dtos
     }
 
+    /// Returns the states in the `App` within a bounding box as dtos.
+    ///
+    /// # Arguments
+    ///
+    /// * `bounding_box` - The bounding box that should be searched in
+    ///
+    /// # Returns
+    ///
+    /// Returns dtos of the states (without distance) within `bounding_box`
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_status_dtos_in_bounding_box_works`
+    pub fn get_status_dtos_in_bounding_box(&self, bounding_box: &BoundingBox) -> Vec<StatusDto> {
+        let snapshot = self.snapshot.load();
+        let current_timestamp = get_current_timestamp();
+
+        snapshot
+            .states
+            .get_nearby(bounding_box)
+            .into_iter()
+            .filter(|status| self.is_current(status, current_timestamp))
+            .filter(|status| bounding_box.contains(&status.position))
+            .map(|status| StatusDto::from(status, None))
+            .collect()
+    }
+
+    /// Returns the states in the `App` within any of given polygons as dtos.
+    ///
+    /// # Arguments
+    ///
+    /// * `polygons` - The polygons that should be searched in
+    ///
+    /// # Returns
+    ///
+    /// Returns dtos of the states (without distance) within any of `polygons`
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_status_dtos_in_polygons_works`
+    pub fn get_status_dtos_in_polygons(&self, polygons: &[Polygon]) -> Vec<StatusDto> {
+        let snapshot = self.snapshot.load();
+        let current_timestamp = get_current_timestamp();
+
+        /* Polygons may overlap, so each aircraft must only be added once */
+        let mut aircraft_ids = HashSet::new();
+
+        polygons
+            .iter()
+            .filter_map(|polygon| Some((polygon, polygon.get_bounding_box()?)))
+            .flat_map(|(polygon, bounding_box)| {
+                snapshot
+                    .states
+                    .get_nearby(&bounding_box)
+                    .into_iter()
+                    .filter(|status| self.is_current(status, current_timestamp))
+                    .filter(|status| polygon.contains(&status.position))
+            })
+            .filter(|status| aircraft_ids.insert(&status.aircraft.id))
+            .map(|status| StatusDto::from(status, None))
+            .collect()
+    }
+
+    /// Returns the state of a single aircraft in the `App` as dto
+    ///
+    /// # Arguments
+    ///
+    /// * `aircraft_id` - Id of the aircraft
+    ///
+    /// # Returns
+    ///
+    /// Returns the dto of the aircraft state (without distance) or `None` if there is no
+    /// current state of the aircraft.
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_status_dto_works`
+    pub fn get_status_dto(&self, aircraft_id: &str) -> Option<StatusDto> {
+        self.snapshot
+            .load()
+            .states
+            .get(aircraft_id)
+            .filter(|status| self.is_current(status, get_current_timestamp()))
+            .map(|status| StatusDto::from(status, None))
+    }
+
+    /// Returns all current states in the `App`, e.g. for outputs that have to catch up after
+    /// missing updates
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_states_works`
+    pub fn get_states(&self) -> Vec<Status> {
+        let current_timestamp = get_current_timestamp();
+
+        self.snapshot
+            .load()
+            .states
+            .iter()
+            .filter(|status| self.is_current(status, current_timestamp))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the track of a single aircraft in the `App`
+    ///
+    /// # Arguments
+    ///
+    /// * `aircraft_id` - Id of the aircraft
+    ///
+    /// # Returns
+    ///
+    /// Returns the recent positions of the aircraft, oldest first, or `None` if there is no
+    /// current state of the aircraft.
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_track_works`
+    /// * test `state::get_track_checks_limits`
+    pub fn get_track(&self, aircraft_id: &str) -> Option<Vec<TrackPoint>> {
+        let snapshot = self.snapshot.load();
+
+        let status = snapshot
+            .states
+            .get(aircraft_id)
+            .filter(|status| self.is_current(status, get_current_timestamp()))?;
+
+        let min_time_stamp = get_current_timestamp()
+            .saturating_sub(self.config.get_track_max_age(status.aircraft_type));
+
+        Some(
+            snapshot
+                .tracks
+                .get(aircraft_id)?
+                .iter()
+                .filter(|point| point.time_stamp >= min_time_stamp)
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Finds known aircraft by registration and / or call sign
+    ///
+    /// # Arguments
+    ///
+    /// * `registration` - Registration to search for, e.g. "D-6507"
+    /// * `call_sign` - Call sign to search for, e.g. "G1"
+    ///
+    /// # Returns
+    ///
+    /// Returns dtos of all aircraft that match all given values, each with its current state
+    /// if there is one.
+    ///
+    /// # Examples
+    ///
+    /// * test `state::find_aircraft_dtos_works`
+    pub fn find_aircraft_dtos(
+        &self,
+        registration: Option<&str>,
+        call_sign: Option<&str>,
+    ) -> Vec<AircraftDto> {
+        let snapshot = self.snapshot.load();
+        let current_timestamp = get_current_timestamp();
+
+        self.aircraft_index
+            .find(registration, call_sign)
+            .into_iter()
+            .map(|aircraft| AircraftDto {
+                id: aircraft.id.clone(),
+                aircraft: aircraft.clone(),
+                state: snapshot
+                    .states
+                    .get(&aircraft.id)
+                    .filter(|status| self.is_current(status, current_timestamp))
+                    .map(|status| StatusDto::from(status, None)),
+            })
+            .collect()
+    }
+
     /// Stores / updates a new status in the `App`
     ///
     /// # Arguments
@@ -103,12 +510,32 @@ impl App {
     ///
     /// * test `state::get_filtered_states_checks_age`
     /// * test `state::get_filtered_states_checks_range`
+    /// * test `state::subscribe_receives_updates`
     pub fn push_status(&self, new_status: Status) {
-        let mut states = self.states.lock().expect("Mutex was poisoned");
+        let _write_lock = self.write_lock.lock().expect("Mutex was poisoned");
+        let mut snapshot = Snapshot::clone(&self.snapshot.load());
 
-        App::remove_outdated_states(&mut states);
+        let track = snapshot
+            .tracks
+            .entry(new_status.aircraft.id.clone())
+            .or_default();
+
+        track.push_back(TrackPoint {
+            position: new_status.position.clone(),
+            altitude: new_status.altitude,
+            speed: new_status.speed,
+            vertical_speed: new_status.vertical_speed,
+            course: new_status.course,
+            time_stamp: new_status.time_stamp,
+        });
 
-        states.insert(new_status.aircraft.id.clone(), new_status);
+        self.remove_outdated_track_points(track, new_status.aircraft_type);
+
+        let expires_at = new_status.time_stamp + self.config.get_max_age(new_status.aircraft_type);
+        snapshot.states.insert(new_status.clone(), expires_at);
+        self.snapshot.store(Arc::new(snapshot));
+
+        self.publish(Change::Stored(new_status));
     }
 
     /// Updates timestamp of latest APRS update in the `App`
@@ -124,6 +551,87 @@ impl App {
         self.last_aprs_update.store(timestamp, Ordering::Relaxed);
     }
 
+    /// Sets the APRS server that is currently connected
+    ///
+    /// # Arguments
+    ///
+    /// * `aprs_server` - Address of the server or `None` if no server is connected
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_overview_works`
+    pub fn push_aprs_server(&self, aprs_server: Option<String>) {
+        *self.aprs_server.lock().expect("Mutex was poisoned") = aprs_server;
+    }
+
+    /// Records that the APRS connection has been dropped as the server stopped responding,
+    /// e.g. nothing was received or connecting timed out
+    ///
+    /// # Arguments
+    ///
+    /// * `timestamp` - Timestamp of dropping the connection
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_overview_works`
+    pub fn push_aprs_stall(&self, timestamp: u64) {
+        self.aprs_stalls.fetch_add(1, Ordering::Relaxed);
+        self.last_aprs_stall.store(timestamp, Ordering::Relaxed);
+    }
+
+    /// Removes outdated states every `Config::expiry_interval` seconds. Never returns.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use api::{App, Config};
+    /// use tokio::spawn;
+    ///
+    /// let app = App::create(Config::default(), ddb::Index::default());
+    ///
+    /// let app_expiry = app.clone();
+    /// spawn(async move { app_expiry.expire_states().await });
+    /// ```
+    pub async fn expire_states(&self) {
+        let mut interval = interval(Duration::from_secs(self.config.expiry_interval));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        loop {
+            interval.tick().await;
+            self.remove_outdated_states();
+        }
+    }
+
+    /// Removes outdated states (by max age) and notifies subscribers that the aircraft are
+    /// lost. Until then, readers skip outdated states.
+    ///
+    /// # Examples
+    ///
+    /// * test `state::remove_outdated_states_works`
+    /// * test `state::subscribe_receives_updates`
+    pub fn remove_outdated_states(&self) {
+        let _write_lock = self.write_lock.lock().expect("Mutex was poisoned");
+        let current_snapshot = self.snapshot.load();
+
+        let outdated_keys = current_snapshot.states.get_expired(get_current_timestamp());
+
+        if outdated_keys.is_empty() {
+            return;
+        }
+
+        let mut snapshot = Snapshot::clone(&current_snapshot);
+
+        for key in outdated_keys {
+            snapshot.tracks.remove(&key);
+
+            if let Some(status) = snapshot.states.remove(&key) {
+                self.publish(Change::Lost(status));
+            }
+        }
+
+        self.snapshot.store(Arc::new(snapshot));
+    }
+
     /// Returns an overview of the currently stored states
     ///
     /// # Examples
@@ -137,48 +645,167 @@ impl App {
             0 => None,
             v => Some(v),
         };
+        let last_aprs_stall = match self.last_aprs_stall.load(Ordering::Relaxed) {
+            0 => None,
+            v => Some(v),
+        };
 
-        let mut states = self.states.lock().expect("Mutex was poisoned");
-        App::remove_outdated_states(&mut states);
+        let states = &self.snapshot.load().states;
+        let current_timestamp = get_current_timestamp();
 
         Overview {
-            count: states.len(),
-            last_status_update: states.values().map(|s| s.time_stamp).max(),
+            count: states.len() - states.get_expired(current_timestamp).len(),
+            last_status_update: states.get_last_time_stamp(current_timestamp),
             last_aprs_update,
+            aprs_server: self.aprs_server.lock().expect("Mutex was poisoned").clone(),
+            aprs_stalls: self.aprs_stalls.load(Ordering::Relaxed),
+            last_aprs_stall,
         }
     }
 
-    /// Removes outdated states (by max age)
+    /// Returns whether a status is not outdated. Outdated states are skipped by readers until
+    /// they're removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status to check
+    /// * `current_timestamp` - The current timestamp
+    fn is_current(&self, status: &Status, current_timestamp: u64) -> bool {
+        status.time_stamp + self.config.get_max_age(status.aircraft_type) >= current_timestamp
+    }
+
+    /// Removes the oldest points of a track that exceed the configured limits
     ///
     /// # Arguments
     ///
-    /// * `states` - `MutexGuard` of states map
-    fn remove_outdated_states(states: &mut MutexGuard<HashMap<String, Status>>) {
+    /// * `track` - The track, oldest point first
+    /// * `aircraft_type` - Type of the aircraft, if known
+    fn remove_outdated_track_points(
+        &self,
+        track: &mut Vector<TrackPoint>,
+        aircraft_type: Option<AircraftType>,
+    ) {
         let current_timestamp = get_current_timestamp();
+        let track_max_age = self.config.get_track_max_age(aircraft_type);
+
+        while track.len() > self.config.track_max_points
+            || track.front().is_some_and(|point| {
+                current_timestamp.saturating_sub(point.time_stamp) > track_max_age
+            })
+        {
+            track.pop_front();
+        }
+    }
+
+    /// Notifies subscribers about a change and keeps it for resuming.
+    /// Must be called while holding the write lock, so that the order of updates matches the
+    /// order of changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `change` - The change of the stored states
+    fn publish(&self, change: Change) {
+        let update = Update {
+            sequence: self.last_update_sequence.fetch_add(1, Ordering::SeqCst) + 1,
+            change,
+        };
+
+        let mut recent_updates = self.recent_updates.lock().expect("Mutex was poisoned");
+
+        if recent_updates.len() >= UPDATE_CHANNEL_CAPACITY {
+            recent_updates.pop_front();
+        }
+
+        recent_updates.push_back(update.clone());
+
+        /* Sending only fails if there are no subscribers, which is fine. */
+        let _ = self.update_tx.send(update);
+    }
+}
+
+/// Sorts dtos in ascending order by their distance
+///
+/// # Arguments
+///
+/// * `status_dtos` - The dtos to sort, all with distance
+fn sort_by_distance(status_dtos: &mut [StatusDto]) {
+    status_dtos.sort_unstable_by(|status_dto_1, status_dto_2| {
+        status_dto_1
+            .distance
+            .partial_cmp(&status_dto_2.distance)
+            .unwrap()
+    });
+}
+
+impl Config {
+    /// Returns the maximum age of states of an aircraft type (in seconds)
+    ///
+    /// # Arguments
+    ///
+    /// * `aircraft_type` - Type of the aircraft, if known
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_max_age_works`
+    pub fn get_max_age(&self, aircraft_type: Option<AircraftType>) -> u64 {
+        aircraft_type
+            .and_then(|aircraft_type| self.max_age_by_type.get(&aircraft_type))
+            .copied()
+            .unwrap_or(self.max_age)
+    }
+
+    /// Returns the maximum age of track positions for an aircraft type (in seconds)
+    ///
+    /// # Arguments
+    ///
+    /// * `aircraft_type` - Type of the aircraft, if known
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_track_is_limited_by_max_age`
+    pub fn get_track_max_age(&self, aircraft_type: Option<AircraftType>) -> u64 {
+        self.track_max_age
+            .unwrap_or_else(|| self.get_max_age(aircraft_type))
+    }
 
-        let outdated_keys = states
+    /// Returns the highest maximum age of states of any aircraft type (in seconds)
+    ///
+    /// # Examples
+    ///
+    /// * test `state::get_max_age_works`
+    pub fn get_max_age_limit(&self) -> u64 {
+        self.max_age_by_type
             .values()
-            .filter(|e| current_timestamp - e.time_stamp > MAX_AGE_DIFF)
-            .map(|e| e.aircraft.id.clone())
-            .collect::<Vec<String>>();
+            .copied()
+            .fold(self.max_age, u64::max)
+    }
+}
 
-        for key in outdated_keys {
-            states.remove(&key);
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            track_max_points: DEFAULT_TRACK_MAX_POINTS,
+            track_max_age: None,
+            expiry_interval: DEFAULT_EXPIRY_INTERVAL,
+            max_age: DEFAULT_MAX_AGE,
+            max_age_by_type: HashMap::new(),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::thread;
+
     use crate::ogn::Aircraft;
 
     use super::*;
 
     #[test]
     fn get_filtered_status_dtos_checks_age() {
-        let sut = App::create();
+        let sut = App::create(Config::default(), ddb::Index::default());
         let current_timestamp = get_current_timestamp();
-        let outdated_timestamp = current_timestamp - MAX_AGE_DIFF - 1;
+        let outdated_timestamp = current_timestamp - DEFAULT_MAX_AGE - 1;
 
         let position = Position {
             longitude: 48.858222,
@@ -203,7 +830,7 @@ mod tests {
 
     #[test]
     fn get_filtered_status_dtos_checks_range() {
-        let sut = App::create();
+        let sut = App::create(Config::default(), ddb::Index::default());
         let current_timestamp = get_current_timestamp();
 
         let position = Position {
@@ -245,7 +872,7 @@ mod tests {
 
     #[test]
     fn get_filtered_status_dtos_orders_correctly() {
-        let sut = App::create();
+        let sut = App::create(Config::default(), ddb::Index::default());
         let current_timestamp = get_current_timestamp();
 
         let position = Position {
@@ -286,11 +913,278 @@ mod tests {
         assert_eq!(result[0].aircraft.id, "AB1234");
         assert_eq!(result[1].aircraft.id, "CD5678");
         assert_eq!(result[2].aircraft.id, "EF9012");
+        assert!(result[2]
+            .bearing
+            .is_some_and(|bearing| (bearing - 33.3).abs() < 0.5));
+        assert!(result[2].compass_point.is_some());
+    }
+
+    #[test]
+    fn get_status_dtos_in_bounding_box_works() {
+        let sut = App::create(Config::default(), ddb::Index::default());
+        let current_timestamp = get_current_timestamp();
+
+        for (aircraft_id, latitude, longitude) in [
+            ("AB1234", 0.0, 175.0),
+            ("CD5678", 0.0, -175.0),
+            ("EF9012", 0.0, 0.0),
+        ] {
+            sut.push_status(create_status(
+                String::from(aircraft_id),
+                Position {
+                    latitude,
+                    longitude,
+                },
+                current_timestamp,
+            ));
+        }
+
+        let result = sut.get_status_dtos_in_bounding_box(&BoundingBox {
+            south: -10.0,
+            west: 170.0,
+            north: 10.0,
+            east: -170.0,
+        });
+
+        assert_eq!(result.len(), 2);
+        assert!(result.iter().any(|s| s.aircraft.id == "AB1234"));
+        assert!(result.iter().any(|s| s.aircraft.id == "CD5678"));
+        assert!(result.iter().all(|s| s.distance.is_none()));
+    }
+
+    #[test]
+    fn get_status_dtos_in_polygons_works() {
+        let sut = App::create(Config::default(), ddb::Index::default());
+        let current_timestamp = get_current_timestamp();
+
+        sut.push_status(create_status(
+            String::from("AB1234"),
+            create_position(),
+            current_timestamp,
+        ));
+
+        sut.push_status(create_status(
+            String::from("CD5678"),
+            Position {
+                latitude: 50.0,
+                longitude: 2.2945,
+            },
+            current_timestamp,
+        ));
+
+        let polygon = Polygon::from(vec![
+            Position {
+                latitude: 48.0,
+                longitude: 2.0,
+            },
+            Position {
+                latitude: 48.0,
+                longitude: 3.0,
+            },
+            Position {
+                latitude: 49.0,
+                longitude: 3.0,
+            },
+            Position {
+                latitude: 49.0,
+                longitude: 2.0,
+            },
+        ]);
+
+        let result = sut.get_status_dtos_in_polygons(&[polygon]);
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].aircraft.id, "AB1234");
+        assert!(sut.get_status_dtos_in_polygons(&[]).is_empty());
+    }
+
+    #[test]
+    fn get_status_dto_works() {
+        let sut = App::create(Config::default(), ddb::Index::default());
+        let current_timestamp = get_current_timestamp();
+
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        sut.push_status(create_status(
+            String::from("AB1234"),
+            position.clone(),
+            current_timestamp,
+        ));
+
+        sut.push_status(create_status(
+            String::from("CD5678"),
+            position.clone(),
+            current_timestamp - DEFAULT_MAX_AGE - 1,
+        ));
+
+        let result = sut.get_status_dto("AB1234");
+        assert!(result.is_some());
+
+        let status_dto = result.unwrap();
+        assert_eq!(status_dto.aircraft.id, "AB1234");
+        assert!(status_dto.distance.is_none());
+
+        assert!(sut.get_status_dto("CD5678").is_none());
+        assert!(sut.get_status_dto("EF9012").is_none());
+    }
+
+    #[test]
+    fn get_states_works() {
+        let sut = App::create(Config::default(), ddb::Index::default());
+        let current_timestamp = get_current_timestamp();
+
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        sut.push_status(create_status(
+            String::from("AB1234"),
+            position.clone(),
+            current_timestamp,
+        ));
+
+        sut.push_status(create_status(
+            String::from("CD5678"),
+            position.clone(),
+            current_timestamp - DEFAULT_MAX_AGE - 1,
+        ));
+
+        let result = sut.get_states();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].aircraft.id, "AB1234");
+    }
+
+    #[test]
+    fn get_track_works() {
+        let sut = App::create(Config::default(), ddb::Index::default());
+        let current_timestamp = get_current_timestamp();
+
+        for i in 0..3 {
+            sut.push_status(create_status(
+                String::from("AB1234"),
+                Position {
+                    latitude: 48.0 + i as f32,
+                    longitude: 2.0,
+                },
+                current_timestamp,
+            ));
+        }
+
+        let result = sut.get_track("AB1234");
+        assert!(result.is_some());
+
+        let track = result.unwrap();
+        assert_eq!(track.len(), 3);
+        assert_eq!(track[0].position.latitude, 48.0);
+        assert_eq!(track[2].position.latitude, 50.0);
+
+        assert!(sut.get_track("CD5678").is_none());
+    }
+
+    #[test]
+    fn get_track_checks_limits() {
+        let config = Config {
+            track_max_points: 2,
+            track_max_age: Some(60),
+            ..Config::default()
+        };
+
+        let sut = App::create(config, ddb::Index::default());
+        let current_timestamp = get_current_timestamp();
+
+        for time_stamp in [
+            current_timestamp - 61,
+            current_timestamp - 30,
+            current_timestamp - 20,
+        ] {
+            sut.push_status(create_status(
+                String::from("AB1234"),
+                create_position(),
+                time_stamp,
+            ));
+        }
+
+        sut.push_status(create_status(
+            String::from("CD5678"),
+            create_position(),
+            current_timestamp - 61,
+        ));
+
+        let track = sut.get_track("AB1234").unwrap();
+        assert_eq!(track.len(), 2);
+        assert_eq!(track[0].time_stamp, current_timestamp - 30);
+        assert_eq!(track[1].time_stamp, current_timestamp - 20);
+
+        assert!(sut.get_track("CD5678").is_some_and(|t| t.is_empty()));
+    }
+
+    #[test]
+    fn get_track_is_limited_by_max_age() {
+        let sut = App::create(Config::default(), ddb::Index::default());
+        let current_timestamp = get_current_timestamp();
+
+        for time_stamp in [
+            current_timestamp - DEFAULT_MAX_AGE - 1,
+            current_timestamp - 20,
+        ] {
+            sut.push_status(create_status(
+                String::from("AB1234"),
+                create_position(),
+                time_stamp,
+            ));
+        }
+
+        let track = sut.get_track("AB1234").unwrap();
+        assert_eq!(track.len(), 1);
+        assert_eq!(track[0].time_stamp, current_timestamp - 20);
+
+        let config = Config::default();
+        assert_eq!(config.get_track_max_age(None), DEFAULT_MAX_AGE);
+        assert_eq!(
+            Config {
+                track_max_age: Some(60 * 30),
+                ..Config::default()
+            }
+            .get_track_max_age(None),
+            60 * 30
+        );
+    }
+
+    #[test]
+    fn find_aircraft_dtos_works() {
+        let aircraft = HashMap::from([
+            create_aircraft("AB1234", "D-6507"),
+            create_aircraft("CD5678", "D-6508"),
+        ]);
+
+        let sut = App::create(Config::default(), ddb::Index::create(Arc::new(aircraft)));
+
+        sut.push_status(create_status(
+            String::from("AB1234"),
+            create_position(),
+            get_current_timestamp(),
+        ));
+
+        let result = sut.find_aircraft_dtos(Some("D-6507"), None);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, "AB1234");
+        assert!(result[0].state.is_some());
+
+        let result = sut.find_aircraft_dtos(Some("D-6508"), None);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, "CD5678");
+        assert!(result[0].state.is_none());
+
+        assert!(sut.find_aircraft_dtos(Some("D-0000"), None).is_empty());
     }
 
     #[test]
     fn get_overview_works() {
-        let sut = App::create();
+        let sut = App::create(Config::default(), ddb::Index::default());
 
         let result_empty = sut.get_overview();
 
@@ -314,16 +1208,282 @@ mod tests {
         ));
 
         sut.push_last_aprs_update_timestamp(current_timestamp);
+        sut.push_aprs_server(Some(String::from("aprs.example.com:14580")));
+        sut.push_aprs_stall(current_timestamp - 10);
+        sut.push_aprs_stall(current_timestamp);
 
         let result_filled = sut.get_overview();
 
         assert_eq!(result_empty.count, 0);
         assert_eq!(result_empty.last_status_update, None);
         assert_eq!(result_empty.last_aprs_update, None);
+        assert_eq!(result_empty.aprs_server, None);
+        assert_eq!(result_empty.aprs_stalls, 0);
+        assert_eq!(result_empty.last_aprs_stall, None);
 
         assert_eq!(result_filled.count, 2);
         assert_eq!(result_filled.last_status_update, Some(current_timestamp));
         assert_eq!(result_filled.last_aprs_update, Some(current_timestamp));
+        assert_eq!(
+            result_filled.aprs_server.as_deref(),
+            Some("aprs.example.com:14580")
+        );
+        assert_eq!(result_filled.aprs_stalls, 2);
+        assert_eq!(result_filled.last_aprs_stall, Some(current_timestamp));
+    }
+
+    #[test]
+    fn remove_outdated_states_works() {
+        let sut = App::create(Config::default(), ddb::Index::default());
+        let current_timestamp = get_current_timestamp();
+
+        sut.push_status(create_status(
+            String::from("AB1234"),
+            create_position(),
+            current_timestamp - DEFAULT_MAX_AGE - 1,
+        ));
+        sut.push_status(create_status(
+            String::from("CD5678"),
+            create_position(),
+            current_timestamp,
+        ));
+
+        /* Outdated states are skipped even before they are removed */
+        assert!(sut.get_status_dto("AB1234").is_none());
+        assert_eq!(sut.get_overview().count, 1);
+
+        sut.remove_outdated_states();
+
+        let snapshot = sut.snapshot.load();
+        assert_eq!(snapshot.states.len(), 1);
+        assert!(snapshot.states.get("CD5678").is_some());
+        assert!(!snapshot.tracks.contains_key("AB1234"));
+
+        /* Nothing left to remove */
+        let last_sequence = sut.get_last_update_sequence();
+        sut.remove_outdated_states();
+        assert_eq!(sut.get_last_update_sequence(), last_sequence);
+    }
+
+    #[test]
+    fn get_max_age_works() {
+        let config = Config {
+            max_age: 300,
+            max_age_by_type: HashMap::from([(AircraftType::Paraglider, 900)]),
+            ..Config::default()
+        };
+
+        assert_eq!(config.get_max_age(Some(AircraftType::Paraglider)), 900);
+        assert_eq!(config.get_max_age(Some(AircraftType::Glider)), 300);
+        assert_eq!(config.get_max_age(None), 300);
+        assert_eq!(config.get_max_age_limit(), 900);
+        assert_eq!(Config::default().get_max_age_limit(), DEFAULT_MAX_AGE);
+    }
+
+    #[test]
+    fn get_extrapolated_status_dtos_works() {
+        let sut = App::create(Config::default(), ddb::Index::default());
+        let current_timestamp = get_current_timestamp();
+
+        /* 3.16 km away, but heading towards the position with 360 km/h for 30 seconds */
+        let mut status = create_status(
+            String::from("AB1234"),
+            Position {
+                latitude: 48.86055,
+                longitude: 2.3376,
+            },
+            current_timestamp - 30,
+        );
+        status.course = Some(265);
+        status.speed = Some(360);
+        sut.push_status(status);
+
+        /* Without course and speed, stays where it is */
+        sut.push_status(create_status(
+            String::from("CD5678"),
+            create_position(),
+            current_timestamp - 30,
+        ));
+
+        assert_eq!(
+            sut.get_filtered_status_dtos(&create_position(), 1.0).len(),
+            1
+        );
+
+        let result = sut.get_extrapolated_status_dtos(&create_position(), 1.0);
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].aircraft.id, "CD5678");
+        assert!(result[0].extrapolation_age.is_none());
+        assert_eq!(result[1].aircraft.id, "AB1234");
+        assert!(result[1].extrapolation_age.is_some_and(|age| age >= 30));
+        assert!(result[1].distance.is_some_and(|distance| distance < 1.0));
+    }
+
+    #[test]
+    fn remove_outdated_states_checks_type() {
+        let config = Config {
+            max_age_by_type: HashMap::from([
+                (AircraftType::Paraglider, DEFAULT_MAX_AGE * 3),
+                (AircraftType::Jet, 60),
+            ]),
+            ..Config::default()
+        };
+
+        let sut = App::create(config, ddb::Index::default());
+        let time_stamp = get_current_timestamp() - DEFAULT_MAX_AGE - 1;
+
+        for (aircraft_id, aircraft_type) in [
+            ("AB1234", Some(AircraftType::Paraglider)),
+            ("CD5678", Some(AircraftType::Jet)),
+            ("EF9012", None),
+        ] {
+            let mut status =
+                create_status(String::from(aircraft_id), create_position(), time_stamp);
+            status.aircraft_type = aircraft_type;
+            sut.push_status(status);
+        }
+
+        let result = sut.get_filtered_status_dtos(&create_position(), 1.0);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].aircraft.id, "AB1234");
+
+        sut.remove_outdated_states();
+
+        let snapshot = sut.snapshot.load();
+        assert_eq!(snapshot.states.len(), 1);
+        assert!(snapshot.states.get("AB1234").is_some());
+    }
+
+    #[test]
+    fn subscribe_receives_updates() {
+        let sut = App::create(Config::default(), ddb::Index::default());
+        let mut update_rx = sut.subscribe();
+        let current_timestamp = get_current_timestamp();
+
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        sut.push_status(create_status(
+            String::from("AB1234"),
+            position.clone(),
+            current_timestamp - DEFAULT_MAX_AGE - 1,
+        ));
+
+        sut.push_status(create_status(
+            String::from("CD5678"),
+            position.clone(),
+            current_timestamp,
+        ));
+
+        sut.remove_outdated_states();
+
+        assert!(matches!(
+            update_rx.try_recv(),
+            Ok(Update { sequence: 1, change: Change::Stored(s) }) if s.aircraft.id == "AB1234"
+        ));
+        assert!(matches!(
+            update_rx.try_recv(),
+            Ok(Update { sequence: 2, change: Change::Stored(s) }) if s.aircraft.id == "CD5678"
+        ));
+        assert!(matches!(
+            update_rx.try_recv(),
+            Ok(Update { sequence: 3, change: Change::Lost(s) }) if s.aircraft.id == "AB1234"
+        ));
+        assert!(update_rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn get_updates_since_works() {
+        let sut = App::create(Config::default(), ddb::Index::default());
+        let current_timestamp = get_current_timestamp();
+
+        let position = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        assert_eq!(sut.get_last_update_sequence(), 0);
+        assert!(sut.get_updates_since(0).is_some_and(|u| u.is_empty()));
+
+        for _ in 0..UPDATE_CHANNEL_CAPACITY + 10 {
+            sut.push_status(create_status(
+                String::from("AB1234"),
+                position.clone(),
+                current_timestamp,
+            ));
+        }
+
+        let last_sequence = sut.get_last_update_sequence();
+        assert_eq!(last_sequence, (UPDATE_CHANNEL_CAPACITY + 10) as u64);
+
+        let updates = sut.get_updates_since(last_sequence - 2).unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!(updates[0].sequence, last_sequence - 1);
+        assert_eq!(updates[1].sequence, last_sequence);
+
+        assert!(sut
+            .get_updates_since(last_sequence)
+            .is_some_and(|u| u.is_empty()));
+        assert!(sut.get_updates_since(5).is_none());
+        assert!(sut.get_updates_since(last_sequence + 1).is_none());
+    }
+
+    #[test]
+    fn reads_see_consistent_snapshots_during_writes() {
+        const AIRCRAFT_COUNT: usize = 1000;
+
+        let sut = App::create(Config::default(), ddb::Index::default());
+        let current_timestamp = get_current_timestamp();
+        let position = create_position();
+
+        thread::scope(|scope| {
+            let writer = scope.spawn(|| {
+                for i in 0..AIRCRAFT_COUNT {
+                    sut.push_status(create_status(
+                        format!("{i:06X}"),
+                        position.clone(),
+                        current_timestamp,
+                    ));
+                }
+            });
+
+            /* Every snapshot contains all states of the ones before */
+            let mut last_count = 0;
+            while !writer.is_finished() {
+                let count = sut.get_filtered_status_dtos(&position, 1.0).len();
+
+                assert!(count >= last_count);
+                last_count = count;
+            }
+        });
+
+        assert_eq!(
+            sut.get_filtered_status_dtos(&position, 1.0).len(),
+            AIRCRAFT_COUNT
+        );
+    }
+
+    fn create_position() -> Position {
+        Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        }
+    }
+
+    fn create_aircraft(aircraft_id: &str, registration: &str) -> (String, Aircraft) {
+        (
+            String::from(aircraft_id),
+            Aircraft {
+                id: String::from(aircraft_id),
+                call_sign: None,
+                registration: Some(String::from(registration)),
+                model: None,
+                visible: true,
+            },
+        )
     }
 
     fn create_status(aircraft_id: String, position: Position, time_stamp: u64) -> Status {
@@ -341,6 +1501,7 @@ mod tests {
             altitude: None,
             turn_rate: None,
             course: None,
+            aircraft_type: None,
             time_stamp,
         }
     }
@@ -0,0 +1,317 @@
+use std::ops::RangeInclusive;
+
+use im::{HashMap, HashSet, OrdSet};
+
+use crate::{
+    aprs::Status,
+    ogn::AircraftId,
+    position::{BoundingBox, Position},
+};
+
+/// Size of the cells of the grid in degrees (about 55 km of latitude)
+const CELL_SIZE_DEGREES: f32 = 0.5;
+
+/// Cell of the grid, as row (by latitude) and column (by longitude)
+type Cell = (i32, i32);
+
+/// Stored states of the aircraft, indexed by the cell of a grid that their position is in, so
+/// that area queries only touch the states of nearby cells. Also keeps the states ordered by
+/// their timestamp and by their expiry, so that neither the latest nor the outdated states
+/// require checking every state.
+///
+/// Built on persistent collections, so cloning is cheap and clones share unchanged data.
+#[derive(Clone, Default)]
+pub struct Store {
+    /// All stored states by aircraft id, each with the timestamp that it expires at
+    states: HashMap<AircraftId, (Status, u64)>,
+    /// Aircraft ids by the cell of their position
+    cells: HashMap<Cell, HashSet<AircraftId>>,
+    /// Timestamp and aircraft id of all stored states, oldest first
+    time_stamps: OrdSet<(u64, AircraftId)>,
+    /// Expiry timestamp and aircraft id of all stored states, earliest first
+    expiries: OrdSet<(u64, AircraftId)>,
+}
+
+impl Store {
+    /// Returns the stored status of an aircraft
+    ///
+    /// # Arguments
+    ///
+    /// * `aircraft_id` - Id of the aircraft
+    pub fn get(&self, aircraft_id: &str) -> Option<&Status> {
+        self.states.get(aircraft_id).map(|(status, _)| status)
+    }
+
+    /// Returns all stored states, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = &Status> {
+        self.states.values().map(|(status, _)| status)
+    }
+
+    /// Returns the number of stored states
+    pub fn len(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Returns the latest timestamp of all states that are not expired
+    ///
+    /// # Arguments
+    ///
+    /// * `current_time_stamp` - The current timestamp
+    ///
+    /// # Returns
+    ///
+    /// The latest timestamp or `None` if there is no state that is not expired
+    ///
+    /// # Examples
+    ///
+    /// * test `store::get_expired_works`
+    pub fn get_last_time_stamp(&self, current_time_stamp: u64) -> Option<u64> {
+        self.time_stamps
+            .iter()
+            .rev()
+            .find(|(_, aircraft_id)| {
+                self.states
+                    .get(aircraft_id)
+                    .is_some_and(|(_, expires_at)| *expires_at >= current_time_stamp)
+            })
+            .map(|(time_stamp, _)| *time_stamp)
+    }
+
+    /// Stores a status, replacing the previous status of the same aircraft
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The status to store
+    /// * `expires_at` - Timestamp after which `status` is outdated
+    ///
+    /// # Examples
+    ///
+    /// * test `store::insert_replaces_status`
+    pub fn insert(&mut self, status: Status, expires_at: u64) {
+        let aircraft_id = status.aircraft.id.clone();
+
+        self.remove(&aircraft_id);
+
+        self.cells
+            .entry(get_cell(&status.position))
+            .or_default()
+            .insert(aircraft_id.clone());
+        self.time_stamps
+            .insert((status.time_stamp, aircraft_id.clone()));
+        self.expiries.insert((expires_at, aircraft_id.clone()));
+        self.states.insert(aircraft_id, (status, expires_at));
+    }
+
+    /// Removes the status of an aircraft
+    ///
+    /// # Arguments
+    ///
+    /// * `aircraft_id` - Id of the aircraft
+    ///
+    /// # Returns
+    ///
+    /// The removed status or `None` if there was no stored status of the aircraft
+    pub fn remove(&mut self, aircraft_id: &str) -> Option<Status> {
+        let (status, expires_at) = self.states.remove(aircraft_id)?;
+        let cell = get_cell(&status.position);
+
+        if let Some(aircraft_ids) = self.cells.get_mut(&cell) {
+            aircraft_ids.remove(aircraft_id);
+
+            if aircraft_ids.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+
+        self.time_stamps
+            .remove(&(status.time_stamp, status.aircraft.id.clone()));
+        self.expiries
+            .remove(&(expires_at, status.aircraft.id.clone()));
+
+        Some(status)
+    }
+
+    /// Returns the ids of all aircraft whose status is expired
+    ///
+    /// # Arguments
+    ///
+    /// * `current_time_stamp` - The current timestamp
+    ///
+    /// # Examples
+    ///
+    /// * test `store::get_expired_works`
+    pub fn get_expired(&self, current_time_stamp: u64) -> Vec<AircraftId> {
+        self.expiries
+            .iter()
+            .take_while(|(expires_at, _)| *expires_at < current_time_stamp)
+            .map(|(_, aircraft_id)| aircraft_id.clone())
+            .collect()
+    }
+
+    /// Returns the states within the cells that a bounding box touches. The result may also
+    /// contain states outside of the bounding box, so callers have to check the positions.
+    ///
+    /// # Arguments
+    ///
+    /// * `bounding_box` - The bounding box to get the states of
+    ///
+    /// # Examples
+    ///
+    /// * test `store::get_nearby_works`
+    pub fn get_nearby(&self, bounding_box: &BoundingBox) -> Vec<&Status> {
+        let rows = get_index(bounding_box.south)..=get_index(bounding_box.north);
+        let columns = if bounding_box.west <= bounding_box.east {
+            vec![get_index(bounding_box.west)..=get_index(bounding_box.east)]
+        } else {
+            /* Bounding box crosses the antimeridian */
+            vec![
+                get_index(bounding_box.west)..=get_index(180.0),
+                get_index(-180.0)..=get_index(bounding_box.east),
+            ]
+        };
+
+        let cell_count = get_length(&rows) * columns.iter().map(get_length).sum::<usize>();
+
+        /* Large boxes touch more cells than there are occupied ones, so rather check those */
+        let aircraft_ids: Vec<&HashSet<AircraftId>> = if cell_count > self.cells.len() {
+            self.cells
+                .iter()
+                .filter(|((row, column), _)| {
+                    rows.contains(row) && columns.iter().any(|c| c.contains(column))
+                })
+                .map(|(_, aircraft_ids)| aircraft_ids)
+                .collect()
+        } else {
+            rows.flat_map(|row| {
+                columns
+                    .iter()
+                    .flat_map(move |c| c.clone().map(move |column| (row, column)))
+            })
+            .filter_map(|cell| self.cells.get(&cell))
+            .collect()
+        };
+
+        aircraft_ids
+            .into_iter()
+            .flatten()
+            .filter_map(|aircraft_id| self.get(aircraft_id))
+            .collect()
+    }
+}
+
+/// Returns the cell of the grid that a position is in
+///
+/// # Arguments
+///
+/// * `position` - The position
+fn get_cell(position: &Position) -> Cell {
+    (get_index(position.latitude), get_index(position.longitude))
+}
+
+/// Returns the row or column of the grid that a latitude or longitude is in
+///
+/// # Arguments
+///
+/// * `degrees` - The latitude or longitude
+fn get_index(degrees: f32) -> i32 {
+    (degrees / CELL_SIZE_DEGREES).floor() as i32
+}
+
+/// Returns the number of rows or columns of a range
+///
+/// # Arguments
+///
+/// * `range` - The range of rows or columns
+fn get_length(range: &RangeInclusive<i32>) -> usize {
+    (range.end() - range.start() + 1).max(0) as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_replaces_status() {
+        let mut sut = Store::default();
+
+        sut.insert(create_status("AB1234", 48.0, 2.0, 1), 11);
+        sut.insert(create_status("AB1234", 10.0, 20.0, 2), 12);
+
+        assert_eq!(sut.len(), 1);
+        assert_eq!(sut.get("AB1234").unwrap().time_stamp, 2);
+        assert_eq!(sut.cells.len(), 1);
+        assert_eq!(sut.time_stamps.len(), 1);
+        assert_eq!(sut.expiries.len(), 1);
+
+        assert!(sut.remove("AB1234").is_some());
+        assert!(sut.remove("AB1234").is_none());
+        assert!(sut.cells.is_empty());
+        assert!(sut.time_stamps.is_empty());
+        assert!(sut.expiries.is_empty());
+    }
+
+    #[test]
+    fn get_expired_works() {
+        let mut sut = Store::default();
+
+        sut.insert(create_status("AB1234", 48.0, 2.0, 3), 13);
+        sut.insert(create_status("CD5678", 48.0, 2.0, 1), 11);
+        /* Newest status, but expires first */
+        sut.insert(create_status("EF9012", 48.0, 2.0, 5), 6);
+
+        assert_eq!(sut.get_expired(12), vec!["EF9012", "CD5678"]);
+        assert!(sut.get_expired(6).is_empty());
+
+        assert_eq!(sut.get_last_time_stamp(6), Some(5));
+        assert_eq!(sut.get_last_time_stamp(12), Some(3));
+        assert_eq!(sut.get_last_time_stamp(14), None);
+    }
+
+    #[test]
+    fn get_nearby_works() {
+        let mut sut = Store::default();
+
+        sut.insert(create_status("AB1234", 48.1, 2.1, 1), 11);
+        sut.insert(create_status("CD5678", 52.0, 13.0, 1), 11);
+        sut.insert(create_status("EF9012", 0.0, 179.9, 1), 11);
+        sut.insert(create_status("AB5678", 0.0, -179.9, 1), 11);
+
+        let result = sut.get_nearby(&BoundingBox {
+            south: 48.0,
+            west: 2.0,
+            north: 48.2,
+            east: 2.2,
+        });
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].aircraft.id, "AB1234");
+
+        let mut result = sut
+            .get_nearby(&BoundingBox {
+                south: -1.0,
+                west: 179.0,
+                north: 1.0,
+                east: -179.0,
+            })
+            .iter()
+            .map(|status| status.aircraft.id.as_str())
+            .collect::<Vec<&str>>();
+        result.sort_unstable();
+        assert_eq!(result, vec!["AB5678", "EF9012"]);
+
+        /* More cells than occupied ones */
+        let result = sut.get_nearby(&BoundingBox {
+            south: -90.0,
+            west: -180.0,
+            north: 90.0,
+            east: 180.0,
+        });
+        assert_eq!(result.len(), 4);
+    }
+
+    fn create_status(aircraft_id: &str, latitude: f32, longitude: f32, time_stamp: u64) -> Status {
+        Status {
+            time_stamp,
+            ..Status::create_for_test(aircraft_id, latitude, longitude)
+        }
+    }
+}
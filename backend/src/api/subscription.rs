@@ -0,0 +1,479 @@
+use std::collections::{HashSet, VecDeque};
+
+use log::warn;
+use serde::Serialize;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+use crate::{
+    ogn::AircraftId,
+    position::{calculate_distance, Position},
+};
+
+use super::{
+    routes::aircraft::StatusDto,
+    state::{App, Change, Update},
+};
+
+/// Subscription on changes of aircraft states within a given area
+pub struct Subscription {
+    /// The `App` that is subscribed to, used to catch up after missing updates
+    app: App,
+    /// Center of the watched area
+    position: Position,
+    /// Range around `position` in km
+    range: f32,
+    /// Ids of aircraft that are currently within the watched area
+    aircraft_ids: HashSet<AircraftId>,
+    /// Receiver of the `App`s updates
+    update_rx: Receiver<Update>,
+    /// Sequence number of the latest update that has been handled
+    sequence: u64,
+    /// Events that are yet to be returned, e.g. after catching up
+    pending_events: VecDeque<Event>,
+}
+
+/// Change of an aircraft within a watched area
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    /// Aircraft entered the watched area
+    Entered { id: AircraftId, state: StatusDto },
+    /// Aircraft within the watched area sent a new status
+    Updated { id: AircraftId, state: StatusDto },
+    /// Aircraft left the watched area
+    Left { id: AircraftId },
+    /// Aircraft within the watched area has been lost as its status is outdated
+    Lost { id: AircraftId },
+}
+
+impl Subscription {
+    /// Creates a new `Subscription` on the states of an `App`
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - The `App` to subscribe to
+    /// * `position` - Center of the area that should be watched
+    /// * `range` - Range around given `position` that should be watched
+    ///
+    /// # Returns
+    ///
+    /// The `Subscription` and `Event::Entered` events for all aircraft that are currently
+    /// within the watched area
+    ///
+    /// # Examples
+    ///
+    /// * test `subscription::create_returns_current_states`
+    pub fn create(app: &App, position: Position, range: f32) -> (Subscription, Vec<Event>) {
+        /* Subscribe first, so that no update gets lost between fetching the current states
+         * and receiving the next updates. */
+        let update_rx = app.subscribe();
+        let sequence = app.get_last_update_sequence();
+
+        let events = app
+            .get_filtered_status_dtos(&position, range)
+            .into_iter()
+            .map(|state| Event::Entered {
+                id: state.aircraft.id.clone(),
+                state,
+            })
+            .collect::<Vec<Event>>();
+
+        let aircraft_ids = events
+            .iter()
+            .map(|event| event.get_id().clone())
+            .collect::<HashSet<AircraftId>>();
+
+        let subscription = Subscription {
+            app: app.clone(),
+            position,
+            range,
+            aircraft_ids,
+            update_rx,
+            sequence,
+            pending_events: VecDeque::new(),
+        };
+
+        (subscription, events)
+    }
+
+    /// Resumes a `Subscription` on the states of an `App` after the update with given
+    /// sequence number
+    ///
+    /// # Arguments
+    ///
+    /// * `app` - The `App` to subscribe to
+    /// * `position` - Center of the area that should be watched
+    /// * `range` - Range around given `position` that should be watched
+    /// * `sequence` - Sequence number of the last update the subscriber has received
+    ///
+    /// # Returns
+    ///
+    /// The `Subscription` and events for all aircraft that changed since `sequence` or `None`
+    /// if those changes are not known anymore
+    ///
+    /// # Examples
+    ///
+    /// * test `subscription::resume_returns_missed_changes`
+    pub fn resume(
+        app: &App,
+        position: Position,
+        range: f32,
+        sequence: u64,
+    ) -> Option<(Subscription, Vec<Event>)> {
+        let update_rx = app.subscribe();
+        let last_sequence = app.get_last_update_sequence();
+
+        let updates = app.get_updates_since(sequence)?;
+
+        let changed_ids = updates
+            .iter()
+            .map(|update| update.change.get_id().clone())
+            .collect::<HashSet<AircraftId>>();
+
+        let states = app.get_filtered_status_dtos(&position, range);
+
+        let aircraft_ids = states
+            .iter()
+            .map(|state| state.aircraft.id.clone())
+            .collect::<HashSet<AircraftId>>();
+
+        /* We don't know what the subscriber has seen before, so every aircraft that changed
+         * is either reported with its current state or as having left. */
+        let mut events = changed_ids
+            .iter()
+            .filter(|id| !aircraft_ids.contains(*id))
+            .map(|id| Event::Left { id: id.clone() })
+            .collect::<Vec<Event>>();
+
+        events.extend(
+            states
+                .into_iter()
+                .filter(|state| changed_ids.contains(&state.aircraft.id))
+                .map(|state| Event::Updated {
+                    id: state.aircraft.id.clone(),
+                    state,
+                }),
+        );
+
+        let subscription = Subscription {
+            app: app.clone(),
+            position,
+            range,
+            aircraft_ids,
+            update_rx,
+            sequence: last_sequence,
+            pending_events: VecDeque::new(),
+        };
+
+        Some((subscription, events))
+    }
+
+    /// Returns the sequence number of the latest update that has been handled
+    pub fn get_sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Returns whether events are pending that belong to the sequence number of the latest
+    /// handled update, e.g. after catching up
+    pub fn has_pending_events(&self) -> bool {
+        !self.pending_events.is_empty()
+    }
+
+    /// Waits for the next change within the watched area. If updates have been missed because
+    /// the subscriber is too slow, the watched area is caught up with the current states of the
+    /// `App` instead.
+    ///
+    /// # Returns
+    ///
+    /// The next `Event` or `None` if the `App` is gone
+    ///
+    /// # Examples
+    ///
+    /// * test `subscription::next_returns_events`
+    /// * test `subscription::next_catches_up_after_lagging`
+    pub async fn next(&mut self) -> Option<Event> {
+        loop {
+            if let Some(event) = self.pending_events.pop_front() {
+                return Some(event);
+            }
+
+            let update = match self.update_rx.recv().await {
+                /* Already contained in the initial states */
+                Ok(update) if update.sequence <= self.sequence => continue,
+                Ok(update) => update,
+                Err(RecvError::Lagged(count)) => {
+                    warn!("Subscription lagged behind, skipped {count} updates. Catching up...");
+                    self.pending_events = self.catch_up();
+                    continue;
+                }
+                Err(RecvError::Closed) => return None,
+            };
+
+            self.sequence = update.sequence;
+
+            if let Some(event) = self.handle_update(update.change) {
+                return Some(event);
+            }
+        }
+    }
+
+    /// Compares the aircraft that are known to be within the watched area with the current
+    /// states of the `App`, so that the subscriber is up to date again after missing updates
+    ///
+    /// # Returns
+    ///
+    /// `Event::Left` or `Event::Lost` for every known aircraft that is not within the watched
+    /// area anymore, `Event::Entered` or `Event::Updated` with the current state for every
+    /// aircraft that is
+    fn catch_up(&mut self) -> VecDeque<Event> {
+        /* The backlog of updates is outdated by the current states. As in `create`, subscribe
+         * first, so that no update gets lost in between. */
+        self.update_rx = self.update_rx.resubscribe();
+        self.sequence = self.app.get_last_update_sequence();
+
+        let states = self
+            .app
+            .get_filtered_status_dtos(&self.position, self.range);
+
+        let aircraft_ids = states
+            .iter()
+            .map(|state| state.aircraft.id.clone())
+            .collect::<HashSet<AircraftId>>();
+
+        let mut events = self
+            .aircraft_ids
+            .difference(&aircraft_ids)
+            .map(|id| match self.app.get_status_dto(id) {
+                Some(_) => Event::Left { id: id.clone() },
+                None => Event::Lost { id: id.clone() },
+            })
+            .collect::<VecDeque<Event>>();
+
+        events.extend(states.into_iter().map(|state| {
+            let id = state.aircraft.id.clone();
+
+            if self.aircraft_ids.contains(&id) {
+                Event::Updated { id, state }
+            } else {
+                Event::Entered { id, state }
+            }
+        }));
+
+        self.aircraft_ids = aircraft_ids;
+
+        events
+    }
+
+    /// Converts a `Change` of the `App` into an `Event` for the watched area
+    ///
+    /// # Arguments
+    ///
+    /// * `change` - The `Change` to convert
+    ///
+    /// # Returns
+    ///
+    /// `None` if the change does not affect the watched area
+    fn handle_update(&mut self, change: Change) -> Option<Event> {
+        match change {
+            Change::Stored(status) => {
+                let distance = calculate_distance(&self.position, &status.position);
+                let id = status.aircraft.id.clone();
+
+                if distance > self.range {
+                    return if self.aircraft_ids.remove(&id) {
+                        Some(Event::Left { id })
+                    } else {
+                        None
+                    };
+                }
+
+                let mut state = StatusDto::from(&status, Some(distance));
+                state.set_direction(&self.position);
+
+                if self.aircraft_ids.insert(id.clone()) {
+                    Some(Event::Entered { id, state })
+                } else {
+                    Some(Event::Updated { id, state })
+                }
+            }
+            Change::Lost(status) => {
+                let id = status.aircraft.id;
+
+                if self.aircraft_ids.remove(&id) {
+                    Some(Event::Lost { id })
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+impl Event {
+    /// Returns the id of the affected aircraft
+    pub fn get_id(&self) -> &AircraftId {
+        match self {
+            Self::Entered { id, .. }
+            | Self::Updated { id, .. }
+            | Self::Left { id }
+            | Self::Lost { id } => id,
+        }
+    }
+
+    /// Returns the name of the event type, e.g. "entered"
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Self::Entered { .. } => "entered",
+            Self::Updated { .. } => "updated",
+            Self::Left { .. } => "left",
+            Self::Lost { .. } => "lost",
+        }
+    }
+}
+
+impl Change {
+    /// Returns the id of the affected aircraft
+    pub fn get_id(&self) -> &AircraftId {
+        match self {
+            Self::Stored(status) => &status.aircraft.id,
+            Self::Lost(status) => &status.aircraft.id,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        api::{state::UPDATE_CHANNEL_CAPACITY, Config},
+        aprs::Status,
+        ogn::ddb,
+    };
+
+    use super::*;
+
+    #[test]
+    fn create_returns_current_states() {
+        let app = App::create(Config::default(), ddb::Index::default());
+
+        app.push_status(Status::create_for_test("AB1234", 48.858222, 2.2945));
+        app.push_status(Status::create_for_test("CD5678", 50.0, 10.0));
+
+        let (_, events) = Subscription::create(&app, create_position(), 4.0);
+
+        assert_eq!(events.len(), 1);
+        assert!(matches!(&events[0], Event::Entered { id, .. } if id == "AB1234"));
+    }
+
+    #[tokio::test]
+    async fn next_returns_events() {
+        let app = App::create(Config::default(), ddb::Index::default());
+        let (mut sut, _) = Subscription::create(&app, create_position(), 4.0);
+
+        /* Outside of area, will be skipped */
+        app.push_status(Status::create_for_test("CD5678", 50.0, 10.0));
+        app.push_status(Status::create_for_test("AB1234", 48.858222, 2.2945));
+        app.push_status(Status::create_for_test("AB1234", 48.86055, 2.3376));
+        app.push_status(Status::create_for_test("AB1234", 50.0, 10.0));
+
+        assert!(matches!(sut.next().await, Some(Event::Entered { id, .. }) if id == "AB1234"));
+        assert!(matches!(sut.next().await, Some(Event::Updated { id, .. }) if id == "AB1234"));
+        assert!(matches!(sut.next().await, Some(Event::Left { id }) if id == "AB1234"));
+    }
+
+    #[tokio::test]
+    async fn next_returns_lost_event() {
+        let app = App::create(Config::default(), ddb::Index::default());
+        let (mut sut, _) = Subscription::create(&app, create_position(), 4.0);
+
+        let mut status = Status::create_for_test("AB1234", 48.858222, 2.2945);
+        status.time_stamp -= 60 * 60;
+
+        app.push_status(status);
+        app.remove_outdated_states();
+
+        assert!(matches!(sut.next().await, Some(Event::Entered { id, .. }) if id == "AB1234"));
+        assert!(matches!(sut.next().await, Some(Event::Lost { id }) if id == "AB1234"));
+    }
+
+    #[tokio::test]
+    async fn next_skips_updates_of_initial_states() {
+        let app = App::create(Config::default(), ddb::Index::default());
+        let update_rx = app.subscribe();
+
+        app.push_status(Status::create_for_test("AB1234", 48.858222, 2.2945));
+
+        /* Simulate that the update was received after fetching the initial states */
+        let (mut sut, _) = Subscription::create(&app, create_position(), 4.0);
+        sut.update_rx = update_rx;
+
+        app.push_status(Status::create_for_test("CD5678", 48.858222, 2.2945));
+
+        assert!(matches!(sut.next().await, Some(Event::Entered { id, .. }) if id == "CD5678"));
+        assert_eq!(sut.get_sequence(), 2);
+    }
+
+    #[tokio::test]
+    async fn next_catches_up_after_lagging() {
+        let app = App::create(Config::default(), ddb::Index::default());
+
+        app.push_status(Status::create_for_test("AB1234", 48.858222, 2.2945));
+        app.push_status(Status::create_for_test("CD5678", 48.858222, 2.2945));
+
+        let (mut sut, _) = Subscription::create(&app, create_position(), 4.0);
+
+        app.push_status(Status::create_for_test("AB1234", 50.0, 10.0));
+        app.push_status(Status::create_for_test("EF9012", 48.858222, 2.2945));
+
+        /* Subscriber is too slow, so the updates above are dropped */
+        for _ in 0..UPDATE_CHANNEL_CAPACITY {
+            app.push_status(Status::create_for_test("GH3456", 50.0, 10.0));
+        }
+
+        let mut events = vec![];
+        for _ in 0..3 {
+            events.push(sut.next().await.unwrap());
+        }
+
+        assert!(matches!(&events[0], Event::Left { id } if id == "AB1234"));
+        assert!(events[1..]
+            .iter()
+            .any(|event| matches!(event, Event::Updated { id, .. } if id == "CD5678")));
+        assert!(events[1..]
+            .iter()
+            .any(|event| matches!(event, Event::Entered { id, .. } if id == "EF9012")));
+        assert_eq!(sut.get_sequence(), app.get_last_update_sequence());
+
+        app.push_status(Status::create_for_test("EF9012", 48.86055, 2.3376));
+
+        assert!(matches!(sut.next().await, Some(Event::Updated { id, .. }) if id == "EF9012"));
+    }
+
+    #[test]
+    fn resume_returns_missed_changes() {
+        let app = App::create(Config::default(), ddb::Index::default());
+
+        app.push_status(Status::create_for_test("AB1234", 48.858222, 2.2945));
+        app.push_status(Status::create_for_test("CD5678", 48.858222, 2.2945));
+        let sequence = app.get_last_update_sequence();
+
+        app.push_status(Status::create_for_test("CD5678", 48.86055, 2.3376));
+        app.push_status(Status::create_for_test("EF9012", 50.0, 10.0));
+
+        let (sut, events) = Subscription::resume(&app, create_position(), 4.0, sequence).unwrap();
+
+        /* EF9012 may have been in range before, so it's reported as having left */
+        assert_eq!(events.len(), 2);
+        assert!(matches!(&events[0], Event::Left { id } if id == "EF9012"));
+        assert!(matches!(&events[1], Event::Updated { id, .. } if id == "CD5678"));
+        assert_eq!(sut.get_sequence(), 4);
+
+        assert!(Subscription::resume(&app, create_position(), 4.0, 99).is_none());
+    }
+
+    fn create_position() -> Position {
+        Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        }
+    }
+}
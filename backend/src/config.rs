@@ -1,7 +1,10 @@
 use config::{ConfigError, Environment, File, FileFormat};
 use serde::Deserialize;
 
-use crate::aprs;
+use crate::{
+    api, aprs,
+    output::{flarm, gdl90, mqtt, sbs, webhook},
+};
 
 /// Name of the config file (".json" is added by the `config` crate automatically)
 pub const PROJECT_CONFIG_FILE: &str = "../config";
@@ -18,6 +21,20 @@ pub struct Config {
     pub ddb_url: String,
     /// Url that the API server should bind to
     pub bind_to: String,
+    /// Config of the states that the API serves
+    #[serde(default)]
+    pub api: api::Config,
+    /// Config of the FLARM NMEA output, disabled if not given
+    pub flarm: Option<flarm::Config>,
+    /// Config of the GDL90 output, disabled if not given
+    pub gdl90: Option<gdl90::Config>,
+    /// Config of the MQTT output, disabled if not given
+    pub mqtt: Option<mqtt::Config>,
+    /// Config of the SBS-1 (BaseStation) output, disabled if not given
+    pub sbs: Option<sbs::Config>,
+    /// Webhooks that are notified when aircraft enter or leave their areas
+    #[serde(default)]
+    pub webhooks: Vec<webhook::Config>,
 }
 
 /// Tries loading configuration from config files or environment
@@ -1,11 +1,22 @@
-use crate::ogn::{aprs, ddb::fetch_aircraft};
+use crate::{
+    ogn::{aprs, ddb},
+    output::{flarm, gdl90, mqtt, sbs, webhook},
+    time::get_current_timestamp,
+};
 use laika::shotgun;
-use log::{error, info};
-use tokio::{select, sync::mpsc, task::JoinSet};
+use log::{error, info, warn};
+use std::sync::Arc;
+use tokio::{
+    select,
+    sync::mpsc,
+    task::JoinSet,
+    time::{sleep, Instant},
+};
 
 mod api;
 mod config;
 mod ogn;
+mod output;
 mod position;
 mod time;
 
@@ -26,10 +37,10 @@ async fn main() {
     };
 
     info!("Loading aircraft data...");
-    let aircraft = match fetch_aircraft(&config.ddb_url).await {
+    let aircraft = match ddb::fetch_aircraft(&config.ddb_url).await {
         Ok(a) => {
             info!("Loaded aircraft data successfully!");
-            a
+            Arc::new(a)
         }
         Err(e) => {
             error!("Could not fetch aircraft data: {e}");
@@ -43,8 +54,64 @@ async fn main() {
     let (status_tx, mut status_rx) = mpsc::channel(32);
     let (line_received_tx, mut line_received_rx) = mpsc::channel(32);
 
-    let app = api::App::create();
+    let app = api::App::create(config.api.clone(), ddb::Index::create(aircraft.clone()));
     let app_update = app.clone();
+    let app_expiry = app.clone();
+    let app_aprs = app.clone();
+
+    /* Outputs are optional and have no graceful shutdown, they're aborted after everything
+     * else has stopped */
+    let mut output_join_set = JoinSet::new();
+
+    if let Some(flarm_config) = config.flarm.clone() {
+        let app = app.clone();
+
+        output_join_set.spawn(async move {
+            info!("Initializing FLARM output...");
+
+            if let Err(e) = flarm::init(flarm_config, app).await {
+                error!("FLARM output stopped with error: {e}");
+            }
+        });
+    }
+
+    if let Some(gdl90_config) = config.gdl90.clone() {
+        let app = app.clone();
+
+        output_join_set.spawn(async move {
+            info!("Initializing GDL90 output...");
+
+            if let Err(e) = gdl90::init(gdl90_config, app).await {
+                error!("GDL90 output stopped with error: {e}");
+            }
+        });
+    }
+
+    if let Some(mqtt_config) = config.mqtt.clone() {
+        let app = app.clone();
+
+        output_join_set.spawn(async move {
+            info!("Initializing MQTT output...");
+            mqtt::init(mqtt_config, app).await;
+        });
+    }
+
+    if let Some(sbs_config) = config.sbs.clone() {
+        let app = app.clone();
+
+        output_join_set.spawn(async move {
+            info!("Initializing SBS-1 output...");
+
+            if let Err(e) = sbs::init(sbs_config, app).await {
+                error!("SBS-1 output stopped with error: {e}");
+            }
+        });
+    }
+
+    for webhook_config in config.webhooks.clone() {
+        info!("Initializing webhook {}...", webhook_config.url);
+        output_join_set.spawn(webhook::init(webhook_config, app.clone()));
+    }
 
     join_set.spawn(async move {
         info!("Initializing API...");
@@ -59,20 +126,70 @@ async fn main() {
     join_set.spawn(async move {
         info!("Initializing APRS client...");
 
+        let mut servers = aprs::Servers::create(&config.aprs.addresses);
+        let mut backoff = aprs::Backoff::create(config.aprs.reconnect.clone());
+
         loop {
-            if let Err(e) = aprs::init(&config.aprs, &status_tx, &line_received_tx, &aircraft).await
-            {
-                error!("Client stopped with error: {e}");
-                break;
+            let address = servers.get_current();
+
+            let result = match aprs::connect(&config.aprs, address).await {
+                Ok(connection) => {
+                    info!("Connected to APRS server {address}");
+                    app_aprs.push_aprs_server(Some(address.clone()));
+
+                    let connected_at = Instant::now();
+                    let result = aprs::init(
+                        connection,
+                        &config.aprs,
+                        &status_tx,
+                        &line_received_tx,
+                        &aircraft,
+                    )
+                    .await;
+
+                    app_aprs.push_aprs_server(None);
+                    backoff.report_connection(connected_at.elapsed());
+                    result
+                }
+                Err(e) => Err(e),
+            };
+
+            match result {
+                /* Server may disconnect us at some point. Just reconnect and carry on. */
+                Ok(()) => info!("Client disconnected"),
+                Err(e) if e.is_fatal() => {
+                    error!("Client stopped with error: {e}");
+                    break;
+                }
+                Err(e) => {
+                    /* Server can't be reached or misbehaves, so rather try the next one */
+                    warn!("Client failed on APRS server {address}: {e}");
+                    servers.switch();
+
+                    if e.is_stall() {
+                        app_aprs.push_aprs_stall(get_current_timestamp());
+                    }
+                }
             }
 
-            /* Server may disconnect us at some point. Just reconnect and carry on. */
-            info!("Client disconnected. Reconnecting...");
+            let Some(delay) = backoff.next_delay() else {
+                error!("Giving up reconnecting to APRS servers");
+                break;
+            };
+
+            info!("Reconnecting in {} ms...", delay.as_millis());
+            sleep(delay).await;
         }
 
         shutdown_tx.send(());
     });
 
+    /* Never stops on its own, so it's aborted after everything else has stopped */
+    output_join_set.spawn(async move {
+        info!("Initializing expiry of outdated states...");
+        app_expiry.expire_states().await;
+    });
+
     join_set.spawn(async move {
         info!("Initializing updates from client to API...");
 
@@ -92,5 +209,6 @@ async fn main() {
     });
 
     while (join_set.join_next().await).is_some() {}
+    output_join_set.abort_all();
     info!("Shutdown");
 }
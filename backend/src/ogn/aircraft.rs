@@ -1,6 +1,6 @@
 use std::fmt::{Display, Formatter, Result};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 /// Representing information about an aircraft.
 #[derive(Clone, Serialize)]
@@ -24,7 +24,7 @@ impl Aircraft {
     ///
     /// # Arguments
     /// * `model` - The new model name that the resulting aircraft
-    ///    should have
+    ///   should have
     ///
     /// # Examples
     ///
@@ -54,7 +54,8 @@ impl Aircraft {
 }
 
 /// Representation of generic aicraft types.
-#[derive(PartialEq)]
+#[derive(Clone, Copy, Deserialize, Eq, Hash, PartialEq)]
+#[serde(rename_all = "snake_case")]
 pub enum Type {
     Glider,
     Tow,
@@ -107,6 +108,32 @@ impl Type {
         }
     }
 
+    /// Returns the APRS aircraft type value of a `Type`. The values equal the aircraft types
+    /// that FLARM uses.
+    ///
+    /// # Examples
+    /// ```
+    /// assert_eq!(Type::Obstacle.to_aprs_u8(), 15);
+    /// assert_eq!(Type::from_aprs_u8(Type::Glider.to_aprs_u8()), Some(Type::Glider));
+    /// ```
+    pub fn to_aprs_u8(self) -> u8 {
+        match self {
+            Self::Glider => 1,
+            Self::Tow => 2,
+            Self::Helicopter => 3,
+            Self::SkyDiver => 4,
+            Self::DropPlane => 5,
+            Self::HangGlider => 6,
+            Self::Paraglider => 7,
+            Self::MotorAircraft => 8,
+            Self::Jet => 9,
+            Self::Balloon => 11,
+            Self::Blimp => 12,
+            Self::Unmanned => 13,
+            Self::Obstacle => 15,
+        }
+    }
+
     /// Returns the (english) name of a `Type`
     ///
     /// # Examples
@@ -1,16 +1,16 @@
-use std::{
-    collections::HashMap,
-    io::{Error, ErrorKind},
-};
+use std::{collections::HashMap, io::ErrorKind, time::Duration};
 
 use log::{debug, error};
-use serde::Deserialize;
+use serde::{de, Deserialize, Deserializer};
 use tokio::io::{AsyncBufReadExt, BufReader, BufWriter};
 use tokio::{
-    io::AsyncWriteExt,
+    io::{self, AsyncRead, AsyncWrite, AsyncWriteExt},
     net::{TcpStream, ToSocketAddrs},
+    pin, select,
     sync::mpsc::Sender,
+    time::{interval_at, sleep, timeout, Instant, MissedTickBehavior},
 };
+use tokio_rustls::client::TlsStream;
 
 use crate::{
     ogn::{Aircraft, AircraftId},
@@ -18,22 +18,37 @@ use crate::{
 };
 
 use super::conversion::convert;
+use super::error::Error;
+use super::reconnect;
 use super::status::Status;
+use super::tls;
 
 /// Messages starting with a hashtag are comments (e.g. keep alive messages)
 const IDENTIFIER_COMMENT: char = '#';
 /// Messages starting with this sequence are connection details
 const IDENTIFIER_TCP_PACKET: &str = "TCPIP*";
-/// Approx. interval of keep alive messages to the server (in seconds)
+/// Messages starting with this sequence are the response of the server to the login
+const IDENTIFIER_LOGIN_RESPONSE: &str = "# logresp ";
+/// Password that is used to login without verification, e.g. for receiving only
+const PASSWORD_UNVERIFIED: &str = "-1";
+/// Interval of keep alive messages to the server (in seconds)
 const KEEPALIVE_INTERVAL_SECONDS: u64 = 60 * 10;
+/// Time that the server has to respond to the login (in seconds)
+const LOGIN_TIMEOUT_SECONDS: u64 = 30;
 /// Keep alive message
 const KEEPALIVE_MESSAGE: &[u8; 12] = b"#keep alive\n";
+/// Default time without any incoming line after which the connection is considered stale (in
+/// seconds). The server sends a keep alive every 20 - 30 seconds.
+const DEFAULT_READ_TIMEOUT_SECONDS: u64 = 60;
 
 /// Configuration for connecting to an APRS server
 #[derive(Deserialize)]
+#[serde(bound(deserialize = "A: Deserialize<'de>"))]
 pub struct Config<A: ToSocketAddrs> {
-    /// Address to connect to, e.g. "aprs.example.com"
-    pub address: A,
+    /// Addresses of the servers to connect to, in order of preference, e.g.
+    /// ["aprs.example.com", "aprs2.example.com"]. A single address may be given as "address".
+    #[serde(alias = "address", deserialize_with = "deserialize_addresses")]
+    pub addresses: Vec<A>,
     /// User name for authentication
     pub user_name: String,
     /// Password for authentication
@@ -42,22 +57,86 @@ pub struct Config<A: ToSocketAddrs> {
     pub client_id: String,
     /// APRS filter that will be applied
     pub filter: Option<String>,
+    /// TLS for the connection, unencrypted if not given
+    pub tls: Option<tls::Config>,
+    /// Delays between reconnects
+    #[serde(default)]
+    pub reconnect: reconnect::Config,
+    /// Seconds without any incoming line after which the connection is dropped. Connecting,
+    /// including the TLS handshake, may not take longer either.
+    #[serde(default = "default_read_timeout")]
+    pub read_timeout: u64,
+}
+
+/// Established connection to an APRS server
+pub enum Connection {
+    /// Unencrypted connection
+    Plain(TcpStream),
+    /// Connection encrypted with TLS
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+/// Connects to an APRS server, encrypted if `Config::tls` is given
+///
+/// # Arguments
+///
+/// * `config` - Information on how to connect
+/// * `address` - Address of the server, one of `Config::addresses`
+///
+/// # Returns
+///
+/// The established `Connection` or an `Error` if the server can't be reached or the TLS
+/// handshake fails (see `tls::connect`). `Error::Connection` with `ErrorKind::TimedOut` if
+/// connecting took longer than `Config::read_timeout` seconds.
+///
+/// # Examples
+///
+/// * test `client::times_out_tls_handshake`
+pub async fn connect<A: ToSocketAddrs>(
+    config: &Config<A>,
+    address: &A,
+) -> Result<Connection, Error> {
+    let connecting = async {
+        let tcp_stream = TcpStream::connect(address).await?;
+
+        match &config.tls {
+            Some(tls_config) => Ok(Connection::Tls(Box::new(
+                tls::connect(tcp_stream, tls_config).await?,
+            ))),
+            None => Ok(Connection::Plain(tcp_stream)),
+        }
+    };
+
+    /* A server that drops packets or never finishes the handshake would block us forever
+     * (or for the timeout of the OS at least), instead of trying the next one */
+    timeout(Duration::from_secs(config.read_timeout), connecting)
+        .await
+        .unwrap_or_else(|_| {
+            Err(Error::Connection(io::Error::new(
+                ErrorKind::TimedOut,
+                format!("Not connected within {} seconds", config.read_timeout),
+            )))
+        })
 }
 
-/// Initiates a `TcpClient` that connects to an APRS server based on given `ClientConfig` and
-/// transmits incoming aircraft states. Sends incoming APRS states via `status_tx`.
+/// Logs in to an APRS server on an established `Connection` and transmits incoming aircraft
+/// states. Sends incoming APRS states via `status_tx`.
 ///
 /// # Arguments
 ///
-/// * `config` - Information on where to connect & login
+/// * `connection` - The connection to the server (see `connect`)
+/// * `config` - Information on how to login
 /// * `status_tx` - A `Sender<String>` that will send incoming states from the server
 /// * `line_received_tx` - A `Sender<u64>` that will send timestamps of incoming APRS
-///    lines from the server
+///   lines from the server
 /// * `aircraft` - Mapping of `AircraftId` => `Aircraft`, necessary for conversion
 ///
 /// # Returns
 ///
-/// Future that will either result to () or Error when an error occurs.
+/// Future that will either result to () when the server closes the connection or `Error` when
+/// an error occurs. `Error::Login` if the server didn't verify a given password,
+/// `Error::LoginTimeout` if the server didn't respond to the login at all, `Error::Stale` if the
+/// server didn't send anything for `Config::read_timeout` seconds.
 ///
 /// # Examples
 ///
@@ -66,13 +145,17 @@ pub struct Config<A: ToSocketAddrs> {
 /// use std::collections::HashMap;
 /// use tokio::{spawn, sync::mpsc::channel};
 ///
-/// let config = aprs::ClientConfig { ... };
+/// let config = aprs::Config { ... };
 /// let (status_tx, status_rx) = channel(32);
 /// let (line_received_tx, line_received_rx) = channel(32);
 /// let aircraft: HashMap<AircraftId, Aircraft> = HashMap::new();
 ///
 /// spawn(async move {
-///     aprs::init(&config, &status_tx, &line_received_tx, &aircraft)
+///     let connection = aprs::connect(&config, &config.addresses[0])
+///         .await
+///         .expect("Could not connect");
+///
+///     aprs::init(connection, &config, &status_tx, &line_received_tx, &aircraft)
 ///         .await
 ///         .expect("Client failed");
 /// });
@@ -82,16 +165,73 @@ pub struct Config<A: ToSocketAddrs> {
 /// }
 /// ```
 pub async fn init<A: ToSocketAddrs>(
+    connection: Connection,
+    config: &Config<A>,
+    status_tx: &Sender<Status>,
+    line_received_tx: &Sender<u64>,
+    aircraft: &HashMap<AircraftId, Aircraft>,
+) -> Result<(), Error> {
+    match connection {
+        Connection::Plain(tcp_stream) => {
+            communicate(tcp_stream, config, status_tx, line_received_tx, aircraft).await
+        }
+        Connection::Tls(tls_stream) => {
+            communicate(tls_stream, config, status_tx, line_received_tx, aircraft).await
+        }
+    }
+}
+
+/// Deserializes either a single address or a non-empty list of addresses
+///
+/// # Arguments
+///
+/// * `deserializer` - The deserializer to read from
+fn deserialize_addresses<'de, D: Deserializer<'de>, A: Deserialize<'de>>(
+    deserializer: D,
+) -> Result<Vec<A>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Addresses<A> {
+        Single(A),
+        Multiple(Vec<A>),
+    }
+
+    match Addresses::deserialize(deserializer)? {
+        Addresses::Single(address) => Ok(vec![address]),
+        Addresses::Multiple(addresses) if addresses.is_empty() => {
+            Err(de::Error::custom("at least one address must be given"))
+        }
+        Addresses::Multiple(addresses) => Ok(addresses),
+    }
+}
+
+/// Logs in to the APRS server on an established connection and transmits incoming aircraft
+/// states until the connection is closed.
+///
+/// # Arguments
+///
+/// * `stream` - The connection to the server, either plain or encrypted
+/// * `config` - Information on how to login
+/// * `status_tx` - A `Sender<String>` that will send incoming states from the server
+/// * `line_received_tx` - A `Sender<u64>` that will send timestamps of incoming APRS
+///   lines from the server
+/// * `aircraft` - Mapping of `AircraftId` => `Aircraft`, necessary for conversion
+///
+/// # Examples
+///
+/// * test `client::sends_keepalive_on_schedule`
+/// * test `client::requires_login_response`
+async fn communicate<S: AsyncRead + AsyncWrite, A: ToSocketAddrs>(
+    stream: S,
     config: &Config<A>,
     status_tx: &Sender<Status>,
     line_received_tx: &Sender<u64>,
     aircraft: &HashMap<AircraftId, Aircraft>,
 ) -> Result<(), Error> {
-    let mut tcp_stream = TcpStream::connect(&config.address).await?;
-    let (mut read_half, mut write_half) = tcp_stream.split();
+    let (read_half, write_half) = io::split(stream);
 
-    let mut tcp_stream_reader = BufReader::new(&mut read_half);
-    let mut tcp_stream_writer = BufWriter::new(&mut write_half);
+    let mut lines = BufReader::new(read_half).lines();
+    let mut tcp_stream_writer = BufWriter::new(write_half);
 
     /* Login to server */
     let login_message = if let Some(filter) = &config.filter {
@@ -106,52 +246,69 @@ pub async fn init<A: ToSocketAddrs>(
         )
     };
 
-    let mut last_keep_alive_timestamp = get_current_timestamp();
-
     tcp_stream_writer
         .write_all(login_message.as_bytes())
         .await?;
     tcp_stream_writer.flush().await?;
 
+    let read_timeout = Duration::from_secs(config.read_timeout);
+    let keepalive_interval = Duration::from_secs(KEEPALIVE_INTERVAL_SECONDS);
+
+    /* Server has to respond to the login in time */
+    let login_deadline = sleep(Duration::from_secs(LOGIN_TIMEOUT_SECONDS));
+    let mut is_logged_in = false;
+
+    /* A half-open connection won't ever be closed, so stop waiting at some point */
+    let stale_deadline = sleep(read_timeout);
+
+    pin!(login_deadline, stale_deadline);
+
+    /* Keep alives are sent on schedule, no matter whether the server sends anything */
+    let mut keepalive = interval_at(Instant::now() + keepalive_interval, keepalive_interval);
+    keepalive.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
     loop {
-        let mut line = String::new();
+        let line = select! {
+            result = lines.next_line() => match result {
+                Ok(Some(line)) => line,
+                Ok(None) => {
+                    debug!("Connection closed");
+                    return Ok(());
+                }
+                Err(e) if e.kind() == ErrorKind::InvalidData => {
+                    /* This may happen */
+                    error!("Error while reading line: {e}");
+                    continue;
+                }
+                Err(e) => return Err(Error::Connection(e)),
+            },
+            _ = keepalive.tick() => {
+                tcp_stream_writer.write_all(KEEPALIVE_MESSAGE).await?;
+                tcp_stream_writer.flush().await?;
 
-        match tcp_stream_reader.read_line(&mut line).await {
-            Ok(0) => {
-                debug!("Connection closed");
-                return Ok(());
-            }
-            Ok(_) => (),
-            Err(e) => {
-                /* This may happen */
-                error!("Error while reading line: {e}");
+                debug!("Sent keep alive");
                 continue;
             }
+            () = &mut login_deadline, if !is_logged_in => {
+                return Err(Error::LoginTimeout(LOGIN_TIMEOUT_SECONDS));
+            }
+            () = &mut stale_deadline => {
+                return Err(Error::Stale(config.read_timeout));
+            }
         };
 
-        let current_timestamp = get_current_timestamp();
+        stale_deadline.as_mut().reset(Instant::now() + read_timeout);
 
         debug!("Got line: '{line}'");
 
         line_received_tx
-            .send(current_timestamp)
+            .send(get_current_timestamp())
             .await
-            .or(Err(Error::new(
-                ErrorKind::Other,
-                "Could not send line received timestamp",
-            )))?;
-
-        /* APRS server sends a keep alive ever 20 - 30 seconds. As we don't want to worry about
-         * *another* async interval shit, we just check if the last keep alive was 10 - 11 minutes
-         * ago and, if so, send a new one. We won't run into a timeout if we're 30 seconds late,
-         * so KISS FTW. */
-        if current_timestamp - last_keep_alive_timestamp >= KEEPALIVE_INTERVAL_SECONDS {
-            last_keep_alive_timestamp = current_timestamp;
-
-            tcp_stream_writer.write_all(KEEPALIVE_MESSAGE).await?;
-            tcp_stream_writer.flush().await?;
-
-            debug!("Sent keep alive");
+            .or(Err(Error::ChannelClosed))?;
+
+        if line.starts_with(IDENTIFIER_LOGIN_RESPONSE) {
+            check_login_response(&line, &config.password)?;
+            is_logged_in = true;
         }
 
         if line.starts_with(IDENTIFIER_COMMENT) || line.contains(IDENTIFIER_TCP_PACKET) {
@@ -166,10 +323,362 @@ pub async fn init<A: ToSocketAddrs>(
 
             debug!("Passing message for aircraft '{}'", status.aircraft.id);
 
-            status_tx
-                .send(status)
+            status_tx.send(status).await.or(Err(Error::ChannelClosed))?;
+        }
+    }
+}
+
+fn default_read_timeout() -> u64 {
+    DEFAULT_READ_TIMEOUT_SECONDS
+}
+
+/// Checks the response of the server to the login, e.g. "# logresp MYC4LLS1GN verified, server
+/// GLIDERN1". A given password that is not verified is wrong, so the login is rejected.
+///
+/// # Arguments
+///
+/// * `line` - The response of the server
+/// * `password` - The password that was sent
+///
+/// # Examples
+///
+/// * test `client::check_login_response_works`
+fn check_login_response(line: &str, password: &str) -> Result<(), Error> {
+    let is_unverified = line
+        .split_whitespace()
+        .nth(3)
+        .is_some_and(|verification| verification.trim_end_matches(',') == "unverified");
+
+    if is_unverified && password != PASSWORD_UNVERIFIED {
+        return Err(Error::Login(String::from(line.trim_end())));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, net::SocketAddr, sync::Arc};
+
+    use rcgen::generate_simple_self_signed;
+    use tokio::{io::duplex, net::TcpListener, sync::mpsc, task::JoinHandle};
+    use tokio_rustls::{
+        rustls::{
+            crypto::ring,
+            pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer},
+            ServerConfig,
+        },
+        TlsAcceptor,
+    };
+
+    use super::*;
+
+    const LINE: &str = "FLRDDE626>APRS,qAS,EGHL:/074548h5111.32N/00102.04W'086/007/A=000607 id0AAB1234 -019fpm +0.0rot 5.5dB 3e -4.3kHz\n";
+
+    #[tokio::test]
+    async fn connects_via_tls() {
+        let (address, ca_file, server) = start_tls_server("connects_via_tls").await;
+        let config = create_config(address, "localhost", Some(ca_file));
+
+        let (status_tx, mut status_rx) = mpsc::channel(8);
+        let (line_received_tx, mut line_received_rx) = mpsc::channel(8);
+
+        let result = connect_and_init(&config, &status_tx, &line_received_tx).await;
+
+        assert!(result.is_ok());
+        assert!(server
+            .await
+            .unwrap()
+            .is_some_and(|login| login == "user MYC4LLS1GN pass 12345 vers test 0.1\n"));
+        assert_eq!(status_rx.recv().await.unwrap().aircraft.id, "AB1234");
+        assert!(line_received_rx.recv().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn drops_stale_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        /* Server accepts the connection, but never sends anything */
+        tokio::spawn(async move {
+            let (_tcp_stream, _) = listener.accept().await.unwrap();
+            sleep(Duration::from_secs(10)).await;
+        });
+
+        let mut config = create_config(address, "localhost", None);
+        config.tls = None;
+        config.read_timeout = 1;
+
+        let (status_tx, _status_rx) = mpsc::channel(8);
+        let (line_received_tx, _line_received_rx) = mpsc::channel(8);
+
+        let result = connect_and_init(&config, &status_tx, &line_received_tx).await;
+
+        assert!(matches!(result, Err(Error::Stale(1))));
+    }
+
+    #[tokio::test]
+    async fn times_out_tls_handshake() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        /* Server accepts the connection, but never answers the handshake */
+        tokio::spawn(async move {
+            let (_tcp_stream, _) = listener.accept().await.unwrap();
+            sleep(Duration::from_secs(10)).await;
+        });
+
+        let mut config = create_config(address, "localhost", None);
+        config.read_timeout = 1;
+
+        let result = connect(&config, &config.addresses[0]).await;
+
+        assert!(matches!(
+            result,
+            Err(Error::Connection(ref e)) if e.kind() == ErrorKind::TimedOut
+        ));
+        assert!(!result.err().unwrap().is_fatal());
+    }
+
+    #[tokio::test]
+    async fn rejects_untrusted_server() {
+        let (status_tx, _status_rx) = mpsc::channel(8);
+        let (line_received_tx, _line_received_rx) = mpsc::channel(8);
+
+        /* Certificate is not valid for the server name */
+        let (address, ca_file, server) = start_tls_server("rejects_untrusted_server").await;
+        let config = create_config(address, "aprs.example.com", Some(ca_file));
+
+        let result = connect_and_init(&config, &status_tx, &line_received_tx).await;
+
+        assert!(result.is_err());
+        assert!(server.await.unwrap().is_none());
+
+        /* Self-signed certificate is not trusted by the root certificates */
+        let (address, _, server) = start_tls_server("rejects_untrusted_server").await;
+        let config = create_config(address, "localhost", None);
+
+        let result = connect_and_init(&config, &status_tx, &line_received_tx).await;
+
+        assert!(result.is_err());
+        assert!(server.await.unwrap().is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sends_keepalive_on_schedule() {
+        let (client_stream, server_stream) = duplex(1024);
+        let mut config = create_config(([127, 0, 0, 1], 14580).into(), "localhost", None);
+        config.read_timeout = 60 * 60;
+
+        let (status_tx, _status_rx) = mpsc::channel(8);
+        let (line_received_tx, _line_received_rx) = mpsc::channel(8);
+
+        /* Server answers the login, but stays silent afterwards */
+        let server = tokio::spawn(async move {
+            let mut reader = BufReader::new(server_stream);
+            let started_at = Instant::now();
+
+            let mut login = String::new();
+            reader.read_line(&mut login).await.unwrap();
+            reader
+                .get_mut()
+                .write_all(b"# logresp MYC4LLS1GN verified, server GLIDERN1\r\n")
                 .await
-                .or(Err(Error::new(ErrorKind::Other, "Could not send status")))?;
+                .unwrap();
+
+            let mut keepalives = vec![];
+            for _ in 0..2 {
+                let mut keepalive = String::new();
+                reader.read_line(&mut keepalive).await.unwrap();
+                keepalives.push((keepalive, started_at.elapsed().as_secs()));
+            }
+
+            /* Hand back the stream, so that the client doesn't see the connection closing */
+            (keepalives, reader)
+        });
+
+        let aircraft = HashMap::new();
+        let client = communicate(
+            client_stream,
+            &config,
+            &status_tx,
+            &line_received_tx,
+            &aircraft,
+        );
+
+        select! {
+            biased;
+
+            result = server => {
+                let (keepalives, _server_stream) = result.unwrap();
+
+                assert_eq!(
+                    keepalives,
+                    vec![
+                        (String::from("#keep alive\n"), KEEPALIVE_INTERVAL_SECONDS),
+                        (String::from("#keep alive\n"), KEEPALIVE_INTERVAL_SECONDS * 2)
+                    ]
+                );
+            }
+            _ = client => {
+                panic!("Connection ended unexpectedly");
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn requires_login_response() {
+        let (client_stream, server_stream) = duplex(1024);
+        let mut config = create_config(([127, 0, 0, 1], 14580).into(), "localhost", None);
+        config.read_timeout = 60 * 60;
+
+        let (status_tx, _status_rx) = mpsc::channel(8);
+        let (line_received_tx, _line_received_rx) = mpsc::channel(8);
+
+        /* Server sends data, but never responds to the login */
+        let server = tokio::spawn(async move {
+            let mut server_stream = server_stream;
+            server_stream.write_all(LINE.as_bytes()).await.unwrap();
+            sleep(Duration::from_secs(60 * 60)).await;
+            server_stream
+        });
+
+        let result = communicate(
+            client_stream,
+            &config,
+            &status_tx,
+            &line_received_tx,
+            &HashMap::new(),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(Error::LoginTimeout(LOGIN_TIMEOUT_SECONDS))
+        ));
+
+        server.abort();
+    }
+
+    #[test]
+    fn check_login_response_works() {
+        let verified = "# logresp MYC4LLS1GN verified, server GLIDERN1\r\n";
+        let unverified = "# logresp MYC4LLS1GN unverified, server GLIDERN1\r\n";
+
+        assert!(check_login_response(verified, "12345").is_ok());
+        assert!(check_login_response(unverified, "-1").is_ok());
+        assert!(matches!(
+            check_login_response(unverified, "12345"),
+            Err(Error::Login(response)) if response == unverified.trim_end()
+        ));
+    }
+
+    #[test]
+    fn deserializes_addresses() {
+        let single: Config<String> = serde_json::from_str(
+            r#"{ "address": "aprs.example.com:14580", "user_name": "MYC4LLS1GN", "password": "12345", "client_id": "test 0.1" }"#,
+        )
+        .unwrap();
+
+        let multiple: Config<String> = serde_json::from_str(
+            r#"{ "addresses": ["aprs.example.com:14580", "aprs2.example.com:14580"], "user_name": "MYC4LLS1GN", "password": "12345", "client_id": "test 0.1" }"#,
+        )
+        .unwrap();
+
+        let empty = serde_json::from_str::<Config<String>>(
+            r#"{ "addresses": [], "user_name": "MYC4LLS1GN", "password": "12345", "client_id": "test 0.1" }"#,
+        );
+
+        assert_eq!(single.addresses, vec!["aprs.example.com:14580"]);
+        assert_eq!(
+            multiple.addresses,
+            vec!["aprs.example.com:14580", "aprs2.example.com:14580"]
+        );
+        assert!(empty.is_err());
+    }
+
+    /// Starts a TLS server with a self-signed certificate for "localhost" that accepts a single
+    /// connection, sends `LINE` after the login and closes the connection.
+    ///
+    /// # Returns
+    ///
+    /// Tuple of the address of the server, path of its certificate and the handle of the server
+    /// task, which results to the received login line (or `None` if the handshake failed).
+    async fn start_tls_server(name: &str) -> (SocketAddr, String, JoinHandle<Option<String>>) {
+        let certified_key = generate_simple_self_signed(vec![String::from("localhost")]).unwrap();
+
+        let ca_file = env::temp_dir().join(format!("above_me_{name}.pem"));
+        fs::write(&ca_file, certified_key.cert.pem()).unwrap();
+
+        let server_config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(
+                vec![certified_key.cert.der().clone()],
+                PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(
+                    certified_key.signing_key.serialize_der(),
+                )),
+            )
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let server = tokio::spawn(async move {
+            let (tcp_stream, _) = listener.accept().await.unwrap();
+            let tls_stream = TlsAcceptor::from(Arc::new(server_config))
+                .accept(tcp_stream)
+                .await
+                .ok()?;
+
+            let mut reader = BufReader::new(tls_stream);
+            let mut login = String::new();
+            reader.read_line(&mut login).await.unwrap();
+
+            let mut tls_stream = reader.into_inner();
+            tls_stream.write_all(LINE.as_bytes()).await.unwrap();
+            tls_stream.shutdown().await.unwrap();
+
+            Some(login)
+        });
+
+        (address, ca_file.to_string_lossy().into_owned(), server)
+    }
+
+    async fn connect_and_init(
+        config: &Config<SocketAddr>,
+        status_tx: &Sender<Status>,
+        line_received_tx: &Sender<u64>,
+    ) -> Result<(), Error> {
+        let connection = connect(config, &config.addresses[0]).await?;
+        init(
+            connection,
+            config,
+            status_tx,
+            line_received_tx,
+            &HashMap::new(),
+        )
+        .await
+    }
+
+    fn create_config(
+        address: SocketAddr,
+        server_name: &str,
+        ca_file: Option<String>,
+    ) -> Config<SocketAddr> {
+        Config {
+            addresses: vec![address],
+            user_name: String::from("MYC4LLS1GN"),
+            password: String::from("12345"),
+            client_id: String::from("test 0.1"),
+            filter: None,
+            tls: Some(tls::Config {
+                server_name: String::from(server_name),
+                ca_file,
+            }),
+            reconnect: reconnect::Config::default(),
+            read_timeout: DEFAULT_READ_TIMEOUT_SECONDS,
         }
     }
 }
@@ -71,23 +71,20 @@ pub fn convert(line: &str, aircraft: &HashMap<AircraftId, Aircraft>) -> Option<S
     };
 
     let id = captures.name("id")?.as_str();
+    let aircraft_type = get_aircraft_type_by_capture(&captures, "type");
 
     let aircraft = if let Some(a) = aircraft.get(id) {
         if a.model.is_some() {
             a.clone()
         } else {
-            let model = get_aircraft_type_by_capture(&captures, "type")
-                .map(|t| t.get_name())
-                .map(String::from);
+            let model = aircraft_type.map(|t| t.get_name()).map(String::from);
 
             a.with_model(model)
         }
     } else {
         debug!("Unknown aircraft id '{id}'");
 
-        let model = get_aircraft_type_by_capture(&captures, "type")
-            .map(|t| t.get_name())
-            .map(String::from);
+        let model = aircraft_type.map(|t| t.get_name()).map(String::from);
 
         Aircraft {
             id: String::from(id),
@@ -109,6 +106,7 @@ pub fn convert(line: &str, aircraft: &HashMap<AircraftId, Aircraft>) -> Option<S
         altitude: capture_as_u16(&captures, "altitude", FACTOR_FT_TO_M),
         turn_rate: capture_as_f32(&captures, "turnRate", FACTOR_TURNS_TWO_MIN_TO_TURNS_MIN),
         course: capture_as_u16(&captures, "course", 1.0),
+        aircraft_type,
         time_stamp: get_current_timestamp(),
     };
 
@@ -222,7 +220,7 @@ fn capture_as_coordinate_value(captures: &Captures, name: &str) -> Option<f32> {
     let value = degrees + minutes;
 
     if orientation == 'S' || orientation == 'W' {
-        Some(value * -1.0)
+        Some(-value)
     } else {
         Some(value)
     }
@@ -425,6 +423,7 @@ mod tests {
         assert!(status.aircraft.call_sign.is_none());
         assert!(status.aircraft.registration.is_none());
         assert!(status.aircraft.model.is_some_and(|v| v == "Tow plane"));
+        assert!(status.aircraft_type.is_some_and(|t| t == AircraftType::Tow));
         assert!(status.aircraft.visible);
     }
 
@@ -0,0 +1,97 @@
+use std::{
+    fmt::{Display, Formatter, Result},
+    io,
+};
+
+/// Enum of `Error`s of the connection to an APRS server
+#[derive(Debug)]
+pub enum Error {
+    /// Server could not be reached or the connection failed
+    Connection(io::Error),
+    /// Server did not send anything for the given number of seconds, so the connection is
+    /// considered stale
+    Stale(u64),
+    /// Server did not accept the login
+    Login(String),
+    /// Server did not respond to the login within the given number of seconds
+    LoginTimeout(u64),
+    /// Configuration is invalid, e.g. the CA file can't be read
+    Config(String),
+    /// Incoming states can't be passed on as their receiver is gone
+    ChannelClosed,
+}
+
+impl Error {
+    /// Returns whether the `Error` is fatal, so that it would occur again on every attempt.
+    /// Connection errors, stale connections and unanswered logins are transient, they may go away
+    /// by reconnecting.
+    ///
+    /// # Examples
+    ///
+    /// * test `error::is_fatal_works`
+    pub fn is_fatal(&self) -> bool {
+        !matches!(
+            self,
+            Self::Connection(_) | Self::Stale(_) | Self::LoginTimeout(_)
+        )
+    }
+
+    /// Returns whether the `Error` is a stall, so that the server stopped responding. This is
+    /// the case for stale connections, unanswered logins and connecting that timed out.
+    ///
+    /// # Examples
+    ///
+    /// * test `error::is_stall_works`
+    pub fn is_stall(&self) -> bool {
+        match self {
+            Self::Stale(_) | Self::LoginTimeout(_) => true,
+            Self::Connection(e) => e.kind() == io::ErrorKind::TimedOut,
+            _ => false,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Self::Connection(e) => write!(f, "Connection failed: {e}"),
+            Self::Stale(seconds) => write!(f, "Nothing received for {seconds} seconds"),
+            Self::Login(response) => write!(f, "Login rejected: {response}"),
+            Self::LoginTimeout(seconds) => {
+                write!(f, "No response to login within {seconds} seconds")
+            }
+            Self::Config(message) => write!(f, "Invalid configuration: {message}"),
+            Self::ChannelClosed => write!(f, "Receiver of states is gone"),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Connection(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_fatal_works() {
+        assert!(!Error::Connection(io::Error::from(io::ErrorKind::ConnectionRefused)).is_fatal());
+        assert!(!Error::Stale(60).is_fatal());
+        assert!(!Error::LoginTimeout(30).is_fatal());
+        assert!(Error::Login(String::from("# logresp MYC4LLS1GN unverified")).is_fatal());
+        assert!(Error::Config(String::from("No CA certificates found")).is_fatal());
+        assert!(Error::ChannelClosed.is_fatal());
+    }
+
+    #[test]
+    fn is_stall_works() {
+        assert!(Error::Stale(60).is_stall());
+        assert!(Error::Connection(io::Error::from(io::ErrorKind::TimedOut)).is_stall());
+        assert!(!Error::Connection(io::Error::from(io::ErrorKind::ConnectionRefused)).is_stall());
+        assert!(Error::LoginTimeout(30).is_stall());
+        assert!(!Error::ChannelClosed.is_stall());
+    }
+}
@@ -0,0 +1,175 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+/// Default delay before the first reconnect (in seconds)
+const DEFAULT_INITIAL_DELAY: u64 = 1;
+/// Default maximum delay between reconnects (in seconds)
+const DEFAULT_MAX_DELAY: u64 = 60 * 5;
+/// Connections that lasted at least this long (in seconds) are considered stable, so the delay
+/// starts over afterwards
+const STABLE_CONNECTION_SECONDS: u64 = 60;
+
+/// Configuration of reconnecting to the APRS servers
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Delay before the first reconnect in _seconds_, doubled with every further attempt
+    pub initial_delay: u64,
+    /// Maximum delay between reconnects in _seconds_
+    pub max_delay: u64,
+    /// Number of reconnects in a row that are attempted before giving up, unlimited if not given.
+    /// Every connection that lasted less than `STABLE_CONNECTION_SECONDS` counts as a failed
+    /// attempt, even if the server closed it cleanly.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            initial_delay: DEFAULT_INITIAL_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+            max_attempts: None,
+        }
+    }
+}
+
+/// Exponential backoff for reconnecting. The delays are jittered, so that many clients don't
+/// hit a recovering server all at once.
+pub struct Backoff {
+    /// Configuration of the delays
+    config: Config,
+    /// Number of attempts in a row that didn't result in a stable connection
+    attempts: u32,
+}
+
+impl Backoff {
+    /// Creates a `Backoff` that starts with `Config::initial_delay`
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Configuration of the delays
+    pub fn create(config: Config) -> Self {
+        Self {
+            config,
+            attempts: 0,
+        }
+    }
+
+    /// Returns the delay before the next attempt. The delay is doubled with every call, up to
+    /// `Config::max_delay`, and then randomly shortened by up to half.
+    ///
+    /// # Returns
+    ///
+    /// The delay or `None` if `Config::max_attempts` reconnects have already been attempted
+    /// without a stable connection, so reconnecting should be given up
+    ///
+    /// # Examples
+    ///
+    /// * test `reconnect::next_delay_increases`
+    /// * test `reconnect::next_delay_gives_up`
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        self.attempts += 1;
+
+        if self
+            .config
+            .max_attempts
+            .is_some_and(|max_attempts| self.attempts > max_attempts)
+        {
+            return None;
+        }
+
+        let factor = 1_u64.checked_shl(self.attempts - 1).unwrap_or(u64::MAX);
+        let delay = self
+            .config
+            .initial_delay
+            .saturating_mul(factor)
+            .min(self.config.max_delay)
+            .saturating_mul(1000);
+
+        let half = delay / 2;
+        Some(Duration::from_millis(half + get_random() % (half + 1)))
+    }
+
+    /// Reports how long a connection lasted. If it was stable, the delay starts over.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - Duration of the connection
+    ///
+    /// # Examples
+    ///
+    /// * test `reconnect::report_connection_resets_when_stable`
+    pub fn report_connection(&mut self, duration: Duration) {
+        if duration >= Duration::from_secs(STABLE_CONNECTION_SECONDS) {
+            self.attempts = 0;
+        }
+    }
+}
+
+/// Returns a random number, which is good enough for jitter (but nothing else)
+fn get_random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_delay_increases() {
+        let mut sut = Backoff::create(Config {
+            initial_delay: 2,
+            max_delay: 10,
+            max_attempts: None,
+        });
+
+        for expected in [2, 4, 8, 10, 10] {
+            let delay = sut.next_delay().unwrap();
+
+            assert!(delay >= Duration::from_secs(expected) / 2);
+            assert!(delay <= Duration::from_secs(expected));
+        }
+
+        /* Doesn't overflow */
+        for _ in 0..100 {
+            assert!(sut.next_delay().unwrap() <= Duration::from_secs(10));
+        }
+    }
+
+    #[test]
+    fn next_delay_gives_up() {
+        let mut sut = Backoff::create(Config {
+            initial_delay: 1,
+            max_delay: 10,
+            max_attempts: Some(3),
+        });
+
+        assert!(sut.next_delay().is_some());
+        assert!(sut.next_delay().is_some());
+        assert!(sut.next_delay().is_some());
+        assert!(sut.next_delay().is_none());
+    }
+
+    #[test]
+    fn report_connection_resets_when_stable() {
+        let mut sut = Backoff::create(Config {
+            initial_delay: 8,
+            max_delay: 100,
+            max_attempts: None,
+        });
+
+        sut.next_delay();
+        sut.next_delay();
+
+        sut.report_connection(Duration::from_secs(1));
+        assert!(sut.next_delay().unwrap() >= Duration::from_secs(16));
+
+        sut.report_connection(Duration::from_secs(STABLE_CONNECTION_SECONDS));
+        assert!(sut.next_delay().unwrap() <= Duration::from_secs(8));
+    }
+}
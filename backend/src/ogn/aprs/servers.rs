@@ -0,0 +1,62 @@
+/// Rotation through the configured APRS servers. The current server is kept as long as it
+/// works, otherwise the next one is tried.
+pub struct Servers<'a, A> {
+    /// Addresses of all servers, in order of preference
+    addresses: &'a [A],
+    /// Index of the current server in `addresses`
+    index: usize,
+}
+
+impl<'a, A> Servers<'a, A> {
+    /// Creates a rotation that starts with the first server
+    ///
+    /// # Arguments
+    ///
+    /// * `addresses` - Addresses of the servers, must not be empty
+    pub fn create(addresses: &'a [A]) -> Self {
+        Self {
+            addresses,
+            index: 0,
+        }
+    }
+
+    /// Returns the address of the current server
+    pub fn get_current(&self) -> &'a A {
+        &self.addresses[self.index]
+    }
+
+    /// Switches to the next server, e.g. because the current one can't be reached or misbehaves
+    ///
+    /// # Examples
+    ///
+    /// * test `servers::switch_wraps_around`
+    pub fn switch(&mut self) {
+        self.index = (self.index + 1) % self.addresses.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switch_wraps_around() {
+        let addresses = ["a", "b", "c"];
+        let mut sut = Servers::create(&addresses);
+
+        assert_eq!(*sut.get_current(), "a");
+
+        sut.switch();
+        assert_eq!(*sut.get_current(), "b");
+
+        sut.switch();
+        sut.switch();
+        assert_eq!(*sut.get_current(), "a");
+
+        let addresses = ["a"];
+        let mut sut = Servers::create(&addresses);
+
+        sut.switch();
+        assert_eq!(*sut.get_current(), "a");
+    }
+}
@@ -1,8 +1,12 @@
 use std::fmt::{Display, Formatter, Result};
 
-use crate::{ogn::Aircraft, position::Position};
+use crate::{
+    ogn::{Aircraft, AircraftType},
+    position::Position,
+};
 
 /// Representation of an aircraft status
+#[derive(Clone)]
 pub struct Status {
     /// Affected aircraft
     pub aircraft: Aircraft,
@@ -18,6 +22,8 @@ pub struct Status {
     pub turn_rate: Option<f32>,
     /// Course of aircraft
     pub course: Option<u16>,
+    /// Generic type of aircraft, as sent via APRS
+    pub aircraft_type: Option<AircraftType>,
     /// Timestamp of receiving status
     pub time_stamp: u64,
 }
@@ -34,6 +40,7 @@ impl Display for Status {
     Altitude: {:?},
     Turn rate: {:?},
     Course: {:?},
+    Aircraft type: {:?},
     Timestamp: {}
 ]",
             self.aircraft,
@@ -43,7 +50,42 @@ impl Display for Status {
             self.altitude,
             self.turn_rate,
             self.course,
+            self.aircraft_type.map(|t| t.get_name()),
             self.time_stamp
         )
     }
 }
+
+#[cfg(test)]
+impl Status {
+    /// Creates a `Status` for tests of a visible aircraft at given position, received right now.
+    /// All other values are empty and can be set with struct update syntax.
+    ///
+    /// # Arguments
+    ///
+    /// * `aircraft_id` - Id of the aircraft
+    /// * `latitude` - Latitude of the aircraft
+    /// * `longitude` - Longitude of the aircraft
+    pub fn create_for_test(aircraft_id: &str, latitude: f32, longitude: f32) -> Status {
+        Status {
+            aircraft: Aircraft {
+                id: String::from(aircraft_id),
+                call_sign: None,
+                registration: None,
+                model: None,
+                visible: true,
+            },
+            position: Position {
+                latitude,
+                longitude,
+            },
+            speed: None,
+            vertical_speed: None,
+            altitude: None,
+            turn_rate: None,
+            course: None,
+            aircraft_type: None,
+            time_stamp: crate::time::get_current_timestamp(),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use serde::Deserialize;
+use tokio::net::TcpStream;
+use tokio_rustls::{
+    client::TlsStream,
+    rustls::{
+        crypto::ring,
+        pki_types::{pem::PemObject, CertificateDer, ServerName},
+        ClientConfig, RootCertStore,
+    },
+    TlsConnector,
+};
+
+use super::error::Error;
+
+/// Configuration of TLS for the connection to an APRS server
+#[derive(Deserialize)]
+pub struct Config {
+    /// Name that the certificate of the server must be valid for (also sent via SNI), e.g.
+    /// "aprs.example.com"
+    pub server_name: String,
+    /// Path to a PEM file with the CA certificates to trust. If not given, the Mozilla root
+    /// certificates are trusted.
+    pub ca_file: Option<String>,
+}
+
+/// Establishes a TLS session on top of a connection to an APRS server
+///
+/// # Arguments
+///
+/// * `tcp_stream` - The connection to the server
+/// * `config` - Server name and trusted CA certificates
+///
+/// # Returns
+///
+/// The encrypted stream, `Error::Config` if the server name is invalid or the CA certificates
+/// can't be loaded or `Error::Connection` if the handshake fails, e.g. because the certificate of
+/// the server is not trusted.
+///
+/// # Examples
+///
+/// * test `client::connects_via_tls`
+pub async fn connect(
+    tcp_stream: TcpStream,
+    config: &Config,
+) -> Result<TlsStream<TcpStream>, Error> {
+    let server_name = ServerName::try_from(config.server_name.clone())
+        .map_err(|e| Error::Config(format!("Invalid server name: {e}")))?;
+
+    let client_config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|e| Error::Config(e.to_string()))?
+        .with_root_certificates(load_root_certificates(config.ca_file.as_deref())?)
+        .with_no_client_auth();
+
+    Ok(TlsConnector::from(Arc::new(client_config))
+        .connect(server_name, tcp_stream)
+        .await?)
+}
+
+/// Loads the CA certificates that are trusted
+///
+/// # Arguments
+///
+/// * `ca_file` - Path to a PEM file with the CA certificates or `None` for the Mozilla root
+///   certificates
+fn load_root_certificates(ca_file: Option<&str>) -> Result<RootCertStore, Error> {
+    let Some(ca_file) = ca_file else {
+        return Ok(RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        });
+    };
+
+    let mut root_certificates = RootCertStore::empty();
+
+    let invalid_ca_file = |e: &dyn std::error::Error| Error::Config(format!("{ca_file}: {e}"));
+
+    for certificate in CertificateDer::pem_file_iter(ca_file).map_err(|e| invalid_ca_file(&e))? {
+        root_certificates
+            .add(certificate.map_err(|e| invalid_ca_file(&e))?)
+            .map_err(|e| invalid_ca_file(&e))?;
+    }
+
+    if root_certificates.is_empty() {
+        return Err(Error::Config(format!(
+            "No CA certificates found in {ca_file}"
+        )));
+    }
+
+    Ok(root_certificates)
+}
@@ -0,0 +1,170 @@
+use std::{collections::HashMap, sync::Arc};
+
+use crate::ogn::{Aircraft, AircraftId};
+
+/// Index of DDB aircraft by registration and call sign
+#[derive(Default)]
+pub struct Index {
+    /// Mapping of `AircraftId` => `Aircraft`
+    aircraft: Arc<HashMap<AircraftId, Aircraft>>,
+    /// Mapping of normalized registration => ids of matching aircraft
+    by_registration: HashMap<String, Vec<AircraftId>>,
+    /// Mapping of normalized call sign => ids of matching aircraft
+    by_call_sign: HashMap<String, Vec<AircraftId>>,
+}
+
+impl Index {
+    /// Creates an `Index` of given aircraft. Aircraft that are not visible are not indexed.
+    ///
+    /// # Arguments
+    ///
+    /// * `aircraft` - Mapping of `AircraftId` => `Aircraft`, as fetched from DDB
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let aircraft = Arc::new(fetch_aircraft(url).await.unwrap());
+    /// let index = Index::create(aircraft.clone());
+    /// ```
+    pub fn create(aircraft: Arc<HashMap<AircraftId, Aircraft>>) -> Index {
+        let mut by_registration: HashMap<String, Vec<AircraftId>> = HashMap::new();
+        let mut by_call_sign: HashMap<String, Vec<AircraftId>> = HashMap::new();
+
+        for a in aircraft.values().filter(|a| a.visible) {
+            if let Some(registration) = &a.registration {
+                by_registration
+                    .entry(normalize(registration))
+                    .or_default()
+                    .push(a.id.clone());
+            }
+
+            if let Some(call_sign) = &a.call_sign {
+                by_call_sign
+                    .entry(normalize(call_sign))
+                    .or_default()
+                    .push(a.id.clone());
+            }
+        }
+
+        Index {
+            aircraft,
+            by_registration,
+            by_call_sign,
+        }
+    }
+
+    /// Finds aircraft by registration and / or call sign. Case, spaces and dashes are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `registration` - Registration to search for, e.g. "D-6507"
+    /// * `call_sign` - Call sign to search for, e.g. "G1"
+    ///
+    /// # Returns
+    ///
+    /// All aircraft that match all given values. Empty if no value is given.
+    ///
+    /// # Examples
+    ///
+    /// * test `index::find_works`
+    pub fn find(&self, registration: Option<&str>, call_sign: Option<&str>) -> Vec<&Aircraft> {
+        let candidates = match (registration, call_sign) {
+            (Some(registration), _) => self.by_registration.get(&normalize(registration)),
+            (None, Some(call_sign)) => self.by_call_sign.get(&normalize(call_sign)),
+            (None, None) => None,
+        };
+
+        let Some(candidates) = candidates else {
+            return vec![];
+        };
+
+        candidates
+            .iter()
+            .filter_map(|id| self.aircraft.get(id))
+            .filter(|a| {
+                call_sign.is_none_or(|call_sign| {
+                    a.call_sign
+                        .as_ref()
+                        .is_some_and(|c| normalize(c) == normalize(call_sign))
+                })
+            })
+            .collect()
+    }
+}
+
+/// Normalizes a registration or call sign, so that e.g. "d 6507" matches "D-6507"
+///
+/// # Arguments
+///
+/// * `value` - The value to normalize
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(normalize("d-65 07"), "D6507");
+/// ```
+fn normalize(value: &str) -> String {
+    value
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_works() {
+        let aircraft = Arc::new(HashMap::from([
+            create_aircraft("AB1234", Some("D-6507"), Some("G1"), true),
+            create_aircraft("CD5678", Some("D-1234"), Some("G1"), true),
+            create_aircraft("EF9012", Some("D-9876"), None, false),
+        ]));
+
+        let sut = Index::create(aircraft);
+
+        let result = sut.find(Some("d 6507"), None);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, "AB1234");
+
+        let mut result = sut.find(None, Some("g1"));
+        result.sort_unstable_by_key(|a| a.id.clone());
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].id, "AB1234");
+        assert_eq!(result[1].id, "CD5678");
+
+        let result = sut.find(Some("D-1234"), Some("G1"));
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].id, "CD5678");
+
+        assert!(sut.find(Some("D-6507"), Some("G2")).is_empty());
+        assert!(sut.find(Some("D-9876"), None).is_empty());
+        assert!(sut.find(None, None).is_empty());
+    }
+
+    #[test]
+    fn normalize_works() {
+        assert_eq!(normalize("d-65 07"), "D6507");
+        assert_eq!(normalize("G1"), "G1");
+    }
+
+    fn create_aircraft(
+        id: &str,
+        registration: Option<&str>,
+        call_sign: Option<&str>,
+        visible: bool,
+    ) -> (AircraftId, Aircraft) {
+        (
+            String::from(id),
+            Aircraft {
+                id: String::from(id),
+                call_sign: call_sign.map(String::from),
+                registration: registration.map(String::from),
+                model: None,
+                visible,
+            },
+        )
+    }
+}
@@ -3,17 +3,25 @@ mod aircraft;
 pub mod aprs {
     mod client;
     mod conversion;
+    mod error;
+    mod reconnect;
+    mod servers;
     mod status;
+    mod tls;
 
-    pub use client::{init, Config};
+    pub use client::{connect, init, Config};
+    pub use reconnect::Backoff;
+    pub use servers::Servers;
     pub use status::Status;
 }
 pub mod ddb {
     mod client;
     mod conversion;
     mod error;
+    mod index;
 
     pub use client::fetch_aircraft;
+    pub use index::Index;
 }
 
 pub use aircraft::Aircraft;
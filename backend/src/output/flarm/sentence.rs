@@ -0,0 +1,277 @@
+use crate::{
+    api::StatusDto,
+    position::{calculate_offset, Position},
+    time::get_date_time,
+};
+
+/// FLARM id type that is reported for all aircraft, as OGN ids are mostly FLARM ids
+const ID_TYPE_FLARM: u8 = 2;
+/// Factor to convert _km/h_ to _m/s_
+const FACTOR_KM_H_TO_M_S: f32 = 1.0 / 3.6;
+/// Factor to convert _turns/min_ to _deg/s_
+const FACTOR_TURNS_MIN_TO_DEG_S: f32 = 360.0 / 60.0;
+
+/// Creates all NMEA sentences that a FLARM device would send for the current states: the
+/// position of the observer (_$GPRMC_, _$GPGGA_), the FLARM status (_$PFLAU_) and one
+/// _$PFLAA_ per aircraft.
+///
+/// # Arguments
+///
+/// * `status_dtos` - The states of the aircraft around the observer
+/// * `observer` - Position of the observer
+/// * `altitude` - Altitude of the observer in _m_
+/// * `time_stamp` - Current timestamp
+///
+/// # Examples
+///
+/// * test `sentence::creates_sentences`
+pub fn create_sentences(
+    status_dtos: &[StatusDto],
+    observer: &Position,
+    altitude: u16,
+    time_stamp: u64,
+) -> Vec<String> {
+    let mut sentences = Vec::with_capacity(status_dtos.len() + 3);
+
+    sentences.push(create_gprmc(observer, time_stamp));
+    sentences.push(create_gpgga(observer, altitude, time_stamp));
+    sentences.push(create_pflau(status_dtos.len()));
+    sentences.extend(
+        status_dtos
+            .iter()
+            .map(|status_dto| create_pflaa(status_dto, observer, altitude)),
+    );
+
+    sentences
+}
+
+/// Creates a _$GPRMC_ sentence (recommended minimum data) for the observer, which is not moving
+///
+/// # Arguments
+///
+/// * `observer` - Position of the observer
+/// * `time_stamp` - Current timestamp
+fn create_gprmc(observer: &Position, time_stamp: u64) -> String {
+    let date_time = get_date_time(time_stamp);
+
+    to_sentence(&format!(
+        "GPRMC,{:02}{:02}{:02}.00,A,{},0.0,0.0,{:02}{:02}{:02},,,A",
+        date_time.hour,
+        date_time.minute,
+        date_time.second,
+        format_position(observer),
+        date_time.day,
+        date_time.month,
+        date_time.year % 100
+    ))
+}
+
+/// Creates a _$GPGGA_ sentence (fix data) for the observer
+///
+/// # Arguments
+///
+/// * `observer` - Position of the observer
+/// * `altitude` - Altitude of the observer in _m_
+/// * `time_stamp` - Current timestamp
+fn create_gpgga(observer: &Position, altitude: u16, time_stamp: u64) -> String {
+    let date_time = get_date_time(time_stamp);
+
+    to_sentence(&format!(
+        "GPGGA,{:02}{:02}{:02}.00,{},1,08,1.0,{}.0,M,0.0,M,,",
+        date_time.hour,
+        date_time.minute,
+        date_time.second,
+        format_position(observer),
+        altitude
+    ))
+}
+
+/// Creates a _$PFLAU_ sentence (FLARM status) of a FLARM device on the ground without alarm
+///
+/// # Arguments
+///
+/// * `count` - Number of received aircraft
+fn create_pflau(count: usize) -> String {
+    to_sentence(&format!("PFLAU,{},1,1,1,0,,0,,,", count.min(99)))
+}
+
+/// Creates a _$PFLAA_ sentence (traffic data) of an aircraft. Values that are not known are
+/// left empty.
+///
+/// # Arguments
+///
+/// * `status_dto` - The state of the aircraft
+/// * `observer` - Position of the observer
+/// * `altitude` - Altitude of the observer in _m_
+fn create_pflaa(status_dto: &StatusDto, observer: &Position, altitude: u16) -> String {
+    let (north, east) = calculate_offset(observer, &status_dto.position);
+
+    let relative_vertical = status_dto
+        .altitude
+        .map(|a| (i32::from(a) - i32::from(altitude)).to_string())
+        .unwrap_or_default();
+
+    let track = status_dto.course.map(|c| c.to_string()).unwrap_or_default();
+
+    let turn_rate = status_dto
+        .turn_rate
+        .map(|t| format!("{:.0}", t * FACTOR_TURNS_MIN_TO_DEG_S))
+        .unwrap_or_default();
+
+    let ground_speed = status_dto
+        .speed
+        .map(|s| format!("{:.0}", f32::from(s) * FACTOR_KM_H_TO_M_S))
+        .unwrap_or_default();
+
+    let climb_rate = status_dto
+        .vertical_speed
+        .map(|v| format!("{v:.1}"))
+        .unwrap_or_default();
+
+    /* 0 equals "unknown" */
+    let aircraft_type = status_dto.aircraft_type.map_or(0, |t| t.to_aprs_u8());
+
+    to_sentence(&format!(
+        "PFLAA,0,{:.0},{:.0},{},{},{},{},{},{},{},{:X}",
+        north * 1000.0,
+        east * 1000.0,
+        relative_vertical,
+        ID_TYPE_FLARM,
+        status_dto.aircraft.id,
+        track,
+        turn_rate,
+        ground_speed,
+        climb_rate,
+        aircraft_type
+    ))
+}
+
+/// Formats a position as NMEA latitude and longitude fields, e.g. "4830.0000,N,00215.0000,E"
+///
+/// # Arguments
+///
+/// * `position` - The position to format
+///
+/// # Examples
+///
+/// * test `sentence::format_position_works`
+fn format_position(position: &Position) -> String {
+    format!(
+        "{},{},{},{}",
+        format_degrees_minutes(position.latitude, 2),
+        if position.latitude < 0.0 { 'S' } else { 'N' },
+        format_degrees_minutes(position.longitude, 3),
+        if position.longitude < 0.0 { 'W' } else { 'E' }
+    )
+}
+
+/// Formats a coordinate as NMEA degrees and minutes, e.g. "4830.0000". The minutes are rounded
+/// before splitting off the degrees, so that they never end up as "60.0000".
+///
+/// # Arguments
+///
+/// * `coordinate` - The coordinate to format, the sign is ignored
+/// * `degree_digits` - Number of digits of the degrees
+fn format_degrees_minutes(coordinate: f32, degree_digits: usize) -> String {
+    /* In ten-thousandths of a minute */
+    let minutes = (f64::from(coordinate.abs()) * 60.0 * 10_000.0).round() as u64;
+
+    format!(
+        "{:0degree_digits$}{:02}.{:04}",
+        minutes / 600_000,
+        minutes / 10_000 % 60,
+        minutes % 10_000
+    )
+}
+
+/// Wraps the content of an NMEA sentence, adding the start delimiter, checksum and line ending
+///
+/// # Arguments
+///
+/// * `content` - Content of the sentence, without "$" and "*"
+///
+/// # Examples
+///
+/// * test `sentence::to_sentence_works`
+fn to_sentence(content: &str) -> String {
+    let checksum = content.bytes().fold(0, |checksum, byte| checksum ^ byte);
+
+    format!("${content}*{checksum:02X}\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{aprs::Status, ogn::AircraftType};
+
+    use super::*;
+
+    #[test]
+    fn creates_sentences() {
+        let observer = Position {
+            latitude: 48.5,
+            longitude: 2.25,
+        };
+
+        let status_dtos = [StatusDto::from(
+            &Status {
+                speed: Some(108),
+                vertical_speed: Some(1.5),
+                altitude: Some(1000),
+                turn_rate: Some(-1.0),
+                course: Some(90),
+                aircraft_type: Some(AircraftType::Glider),
+                time_stamp: 0,
+                ..Status::create_for_test("AB1234", 48.51, 2.29)
+            },
+            None,
+        )];
+
+        let result = create_sentences(&status_dtos, &observer, 100, 1_709_210_096);
+
+        assert_eq!(result.len(), 4);
+        assert!(
+            result[0].starts_with("$GPRMC,123456.00,A,4830.0000,N,00215.0000,E,0.0,0.0,290224,")
+        );
+        assert!(
+            result[1].starts_with("$GPGGA,123456.00,4830.0000,N,00215.0000,E,1,08,1.0,100.0,M,")
+        );
+        assert!(result[2].starts_with("$PFLAU,1,1,1,1,0,,0,,,*"));
+        assert!(result[3].starts_with("$PFLAA,0,1112,2947,900,2,AB1234,90,-6,30,1.5,1*"));
+        assert!(result.iter().all(|sentence| sentence.ends_with("\r\n")));
+    }
+
+    #[test]
+    fn format_position_works() {
+        assert_eq!(
+            format_position(&Position {
+                latitude: 48.5,
+                longitude: 2.25
+            }),
+            "4830.0000,N,00215.0000,E"
+        );
+        assert_eq!(
+            format_position(&Position {
+                latitude: -33.5,
+                longitude: -151.25
+            }),
+            "3330.0000,S,15115.0000,W"
+        );
+
+        /* Minutes that round up to 60 carry over to the degrees */
+        assert_eq!(
+            format_position(&Position {
+                latitude: 0.999_999_5,
+                longitude: 0.999_999_5
+            }),
+            "0100.0000,N,00100.0000,E"
+        );
+    }
+
+    #[test]
+    fn to_sentence_works() {
+        assert_eq!(
+            to_sentence("PFLAU,3,1,2,1,0,,0,,,"),
+            String::from("$PFLAU,3,1,2,1,0,,0,,,*4F\r\n")
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use std::{io::Error, net::SocketAddr, sync::Arc, time::Duration};
+
+use log::{debug, info};
+use serde::Deserialize;
+use tokio::{
+    io::AsyncWriteExt,
+    net::{TcpListener, TcpStream},
+    time::interval,
+};
+
+use crate::{api::App, position::Position, time::get_current_timestamp};
+
+use super::sentence::create_sentences;
+
+/// Interval of sending traffic to clients (in seconds), equals the rate of FLARM devices
+const SEND_INTERVAL_SECONDS: u64 = 1;
+
+/// Configuration of the FLARM NMEA output
+#[derive(Clone, Deserialize)]
+pub struct Config {
+    /// Address that the TCP server should bind to, e.g. "0.0.0.0:4353"
+    pub bind_to: String,
+    /// Position of the observer (ownship) that traffic is reported relative to
+    pub observer: Position,
+    /// Altitude of the observer in _m_
+    pub altitude: u16,
+    /// Range around the observer in _km_ that aircraft are reported in
+    pub range: f32,
+}
+
+/// Initializes a TCP server that sends FLARM compatible NMEA sentences of the aircraft around
+/// the configured observer to every connected client, e.g. XCSoar or LK8000.
+///
+/// # Arguments
+///
+/// * `config` - Configuration of the output
+/// * `app` - The `App` to get the current states from
+///
+/// # Returns
+///
+/// Future that will only result to Error, if the server could not be started.
+///
+/// # Examples
+///
+/// ```
+/// use output::flarm;
+/// use tokio::spawn;
+///
+/// let app = App::create(Config::default(), ddb::Index::default());
+///
+/// spawn(async move {
+///     flarm::init(config.flarm, app)
+///         .await
+///         .expect("FLARM output failed");
+/// });
+/// ```
+pub async fn init(config: Config, app: App) -> Result<(), Error> {
+    let listener = TcpListener::bind(&config.bind_to).await?;
+    let config = Arc::new(config);
+
+    info!("FLARM output listening on {}", config.bind_to);
+
+    loop {
+        let (stream, address) = listener.accept().await?;
+
+        debug!("FLARM client {address} connected");
+
+        tokio::spawn(handle(stream, address, config.clone(), app.clone()));
+    }
+}
+
+/// Sends the current traffic to a connected client regularly, until it disconnects
+///
+/// # Arguments
+///
+/// * `stream` - The `TcpStream` of the client
+/// * `address` - Address of the client
+/// * `config` - Configuration of the output
+/// * `app` - The `App` to get the current states from
+async fn handle(mut stream: TcpStream, address: SocketAddr, config: Arc<Config>, app: App) {
+    let mut interval = interval(Duration::from_secs(SEND_INTERVAL_SECONDS));
+
+    loop {
+        interval.tick().await;
+
+        let status_dtos = app.get_filtered_status_dtos(&config.observer, config.range);
+        let sentences = create_sentences(
+            &status_dtos,
+            &config.observer,
+            config.altitude,
+            get_current_timestamp(),
+        );
+
+        if let Err(e) = stream.write_all(sentences.concat().as_bytes()).await {
+            debug!("FLARM client {address} disconnected: {e}");
+            return;
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::{io::Error, time::Duration};
+
+use log::{info, warn};
+use serde::Deserialize;
+use tokio::{net::UdpSocket, time::interval};
+
+use crate::{api::App, position::Position, time::get_current_timestamp};
+
+use super::message::{create_heartbeat, create_ownship_report, create_traffic_report};
+
+/// Interval of broadcasting traffic (in seconds), as required for heartbeat messages
+const SEND_INTERVAL_SECONDS: u64 = 1;
+/// Address that the UDP socket binds to, any local address and port
+const LOCAL_ADDRESS: &str = "0.0.0.0:0";
+
+/// Configuration of the GDL90 output
+#[derive(Clone, Deserialize)]
+pub struct Config {
+    /// Address that the messages are sent to, e.g. "192.168.1.255:4000"
+    pub address: String,
+    /// Position of the observer that is reported as ownship
+    pub observer: Position,
+    /// Altitude of the observer in _m_
+    pub altitude: u16,
+    /// Range around the observer in _km_ that aircraft are reported in
+    pub range: f32,
+}
+
+/// Initializes a UDP broadcaster that sends GDL90 messages of the aircraft around the configured
+/// observer every second, e.g. to EFB apps like ForeFlight, SkyDemon or EasyVFR.
+///
+/// # Arguments
+///
+/// * `config` - Configuration of the output
+/// * `app` - The `App` to get the current states from
+///
+/// # Returns
+///
+/// Future that will only result to Error, if the socket could not be set up.
+///
+/// # Examples
+///
+/// ```
+/// use output::gdl90;
+/// use tokio::spawn;
+///
+/// let app = App::create(Config::default(), ddb::Index::default());
+///
+/// spawn(async move {
+///     gdl90::init(config.gdl90, app)
+///         .await
+///         .expect("GDL90 output failed");
+/// });
+/// ```
+pub async fn init(config: Config, app: App) -> Result<(), Error> {
+    let socket = UdpSocket::bind(LOCAL_ADDRESS).await?;
+    /* Target is usually the broadcast address of the local network */
+    socket.set_broadcast(true)?;
+    socket.connect(&config.address).await?;
+
+    info!("GDL90 output sending to {}", config.address);
+
+    let mut interval = interval(Duration::from_secs(SEND_INTERVAL_SECONDS));
+
+    loop {
+        interval.tick().await;
+
+        let mut messages = vec![
+            create_heartbeat(get_current_timestamp()),
+            create_ownship_report(&config.observer, config.altitude),
+        ];
+
+        messages.extend(
+            app.get_filtered_status_dtos(&config.observer, config.range)
+                .iter()
+                .map(create_traffic_report),
+        );
+
+        for message in messages {
+            /* Network may be unavailable for some time. Just try again on next interval. */
+            if let Err(e) = socket.send(&message).await {
+                warn!("Could not send GDL90 message: {e}");
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,407 @@
+use crate::{api::StatusDto, ogn::AircraftType, position::Position};
+
+/// Flag byte that starts and ends every frame
+const FLAG_BYTE: u8 = 0x7E;
+/// Byte that escapes flag and control bytes within a frame
+const CONTROL_ESCAPE_BYTE: u8 = 0x7D;
+/// Value that escaped bytes are XORed with
+const ESCAPE_XOR: u8 = 0x20;
+/// Id of the heartbeat message
+const MESSAGE_ID_HEARTBEAT: u8 = 0;
+/// Id of the ownship report message
+const MESSAGE_ID_OWNSHIP_REPORT: u8 = 10;
+/// Id of the traffic report message
+const MESSAGE_ID_TRAFFIC_REPORT: u8 = 20;
+/// Address type "ADS-B with self-assigned address", as OGN ids are mostly no ICAO addresses
+const ADDRESS_TYPE_SELF_ASSIGNED: u8 = 1;
+/// Call sign of the ownship
+const OWNSHIP_CALL_SIGN: &str = "ABOVEME";
+/// Resolution of latitude and longitude values (in degrees)
+const RESOLUTION_COORDINATE: f32 = 180.0 / 8_388_608.0; /* 2^23 */
+/// Factor to convert _m_ to _ft_
+const FACTOR_M_TO_FT: f32 = 1.0 / 0.3048;
+/// Factor to convert _km/h_ to _knots_
+const FACTOR_KM_H_TO_KNOTS: f32 = 1.0 / 1.852;
+/// Factor to convert _m/s_ to _ft/min_
+const FACTOR_M_S_TO_FT_MIN: f32 = 60.0 / 0.3048;
+/// Value of 12 bit fields that are not known
+const INVALID_12_BIT: u16 = 0xFFF;
+/// Value of the vertical velocity field if it's not known
+const INVALID_VERTICAL_VELOCITY: u16 = 0x800;
+/// Lookup table of the CRC-16-CCITT
+const CRC_TABLE: [u16; 256] = create_crc_table();
+
+/// Creates a framed heartbeat message
+///
+/// # Arguments
+///
+/// * `time_stamp` - Current timestamp
+///
+/// # Examples
+///
+/// * test `message::creates_heartbeat`
+pub fn create_heartbeat(time_stamp: u64) -> Vec<u8> {
+    /* Seconds since midnight UTC, 17 bits */
+    let seconds = (time_stamp % 86400) as u32;
+
+    let [seconds_low, seconds_high, seconds_bit_16, _] = seconds.to_le_bytes();
+
+    let message = [
+        MESSAGE_ID_HEARTBEAT,
+        /* GPS position valid, UAT initialized */
+        0b1000_0001,
+        /* Time stamp bit 16, UTC OK */
+        (seconds_bit_16 << 7) | 0b0000_0001,
+        seconds_low,
+        seconds_high,
+        /* No uplink or basic / long messages received */
+        0,
+        0,
+    ];
+
+    frame(&message)
+}
+
+/// Creates a framed ownship report of the observer, which is not moving
+///
+/// # Arguments
+///
+/// * `observer` - Position of the observer
+/// * `altitude` - Altitude of the observer in _m_
+pub fn create_ownship_report(observer: &Position, altitude: u16) -> Vec<u8> {
+    let message = create_report(
+        MESSAGE_ID_OWNSHIP_REPORT,
+        0,
+        &Report {
+            position: observer,
+            altitude: Some(altitude),
+            speed: Some(0),
+            vertical_speed: Some(0.0),
+            course: None,
+            airborne: false,
+            emitter_category: 0,
+            call_sign: OWNSHIP_CALL_SIGN,
+        },
+    );
+
+    frame(&message)
+}
+
+/// Creates a framed traffic report of an aircraft
+///
+/// # Arguments
+///
+/// * `status_dto` - The state of the aircraft
+///
+/// # Examples
+///
+/// * test `message::creates_traffic_report`
+pub fn create_traffic_report(status_dto: &StatusDto) -> Vec<u8> {
+    /* Aircraft ids are hex values */
+    let address = u32::from_str_radix(&status_dto.aircraft.id, 16).unwrap_or_default();
+
+    let call_sign = status_dto
+        .aircraft
+        .registration
+        .as_ref()
+        .or(status_dto.aircraft.call_sign.as_ref())
+        .map_or("", String::as_str);
+
+    let message = create_report(
+        MESSAGE_ID_TRAFFIC_REPORT,
+        address,
+        &Report {
+            position: &status_dto.position,
+            altitude: status_dto.altitude,
+            speed: status_dto.speed,
+            vertical_speed: status_dto.vertical_speed,
+            course: status_dto.course,
+            airborne: true,
+            emitter_category: get_emitter_category(status_dto.aircraft_type),
+            call_sign,
+        },
+    );
+
+    frame(&message)
+}
+
+/// Values of an ownship or traffic report
+struct Report<'a> {
+    /// Position of the aircraft
+    position: &'a Position,
+    /// Altitude in _m_
+    altitude: Option<u16>,
+    /// Speed in _km/h_
+    speed: Option<u16>,
+    /// Vertical speed in _m/s_
+    vertical_speed: Option<f32>,
+    /// Course of aircraft
+    course: Option<u16>,
+    /// Is the aircraft airborne?
+    airborne: bool,
+    /// GDL90 emitter category
+    emitter_category: u8,
+    /// Call sign, only the first 8 characters are used
+    call_sign: &'a str,
+}
+
+/// Creates an (unframed) ownship or traffic report message, as both share the same layout
+///
+/// # Arguments
+///
+/// * `message_id` - Id of the message
+/// * `address` - 24 bit address of the aircraft
+/// * `report` - The values of the report
+///
+/// # Examples
+///
+/// * test `message::create_report_limits_altitude`
+fn create_report(message_id: u8, address: u32, report: &Report) -> Vec<u8> {
+    let mut message = Vec::with_capacity(28);
+
+    message.push(message_id);
+    /* No traffic alert */
+    message.push(ADDRESS_TYPE_SELF_ASSIGNED);
+    message.extend_from_slice(&address.to_be_bytes()[1..]);
+    message.extend_from_slice(&encode_coordinate(report.position.latitude));
+    message.extend_from_slice(&encode_coordinate(report.position.longitude));
+
+    /* Altitude in 25 ft steps with an offset of -1000 ft, up to 101350 ft */
+    let altitude = report.altitude.map_or(INVALID_12_BIT, |a| {
+        (((f32::from(a) * FACTOR_M_TO_FT + 1000.0) / 25.0).round() as u16).min(INVALID_12_BIT - 1)
+    });
+
+    /* Report is updated, true track angle (if known), airborne or on ground */
+    let misc = (u16::from(report.airborne) << 3) | u16::from(report.course.is_some());
+
+    message.extend_from_slice(&((altitude << 4) | misc).to_be_bytes());
+    /* NIC and NACp are not known */
+    message.push(0);
+
+    let horizontal_velocity = report.speed.map_or(INVALID_12_BIT, |s| {
+        ((f32::from(s) * FACTOR_KM_H_TO_KNOTS).round() as u16).min(INVALID_12_BIT - 1)
+    });
+
+    /* Vertical velocity in 64 ft/min steps, 12 bit signed */
+    let vertical_velocity = report
+        .vertical_speed
+        .map_or(INVALID_VERTICAL_VELOCITY, |v| {
+            ((v * FACTOR_M_S_TO_FT_MIN / 64.0)
+                .round()
+                .clamp(-510.0, 510.0) as i16 as u16)
+                & INVALID_12_BIT
+        });
+
+    let [_, velocity_high, velocity_middle, velocity_low] =
+        ((u32::from(horizontal_velocity) << 12) | u32::from(vertical_velocity)).to_be_bytes();
+    message.extend_from_slice(&[velocity_high, velocity_middle, velocity_low]);
+
+    /* Track in 360/256 degree steps */
+    let track = report.course.map_or(0, |c| {
+        (f32::from(c % 360) * 256.0 / 360.0).round() as u16 as u8
+    });
+    message.push(track);
+
+    message.push(report.emitter_category);
+
+    let mut call_sign = report
+        .call_sign
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase() as u8)
+        .take(8)
+        .collect::<Vec<u8>>();
+    call_sign.resize(8, b' ');
+    message.extend_from_slice(&call_sign);
+
+    /* No emergency */
+    message.push(0);
+
+    message
+}
+
+/// Encodes a latitude or longitude as 24 bit signed value
+///
+/// # Arguments
+///
+/// * `value` - The latitude or longitude
+fn encode_coordinate(value: f32) -> [u8; 3] {
+    let [_, high, middle, low] = ((value / RESOLUTION_COORDINATE).round() as i32).to_be_bytes();
+
+    [high, middle, low]
+}
+
+/// Returns the GDL90 emitter category that represents an aircraft type best
+///
+/// # Arguments
+///
+/// * `aircraft_type` - Type of the aircraft, if known
+fn get_emitter_category(aircraft_type: Option<AircraftType>) -> u8 {
+    match aircraft_type {
+        Some(AircraftType::Glider) => 9,
+        Some(AircraftType::Tow | AircraftType::DropPlane | AircraftType::MotorAircraft) => 1,
+        Some(AircraftType::Jet) => 3,
+        Some(AircraftType::Helicopter) => 7,
+        Some(AircraftType::SkyDiver) => 11,
+        Some(AircraftType::HangGlider | AircraftType::Paraglider) => 12,
+        Some(AircraftType::Balloon | AircraftType::Blimp) => 10,
+        Some(AircraftType::Unmanned) => 14,
+        Some(AircraftType::Obstacle) => 19,
+        None => 0,
+    }
+}
+
+/// Frames a message: appends its CRC, escapes flag and control bytes and surrounds it with
+/// flag bytes
+///
+/// # Arguments
+///
+/// * `message` - The message, starting with the message id
+///
+/// # Examples
+///
+/// * test `message::creates_heartbeat`
+fn frame(message: &[u8]) -> Vec<u8> {
+    let crc = calculate_crc(message);
+
+    let mut frame = Vec::with_capacity(message.len() * 2 + 4);
+    frame.push(FLAG_BYTE);
+
+    for &byte in message.iter().chain(crc.to_le_bytes().iter()) {
+        if byte == FLAG_BYTE || byte == CONTROL_ESCAPE_BYTE {
+            frame.push(CONTROL_ESCAPE_BYTE);
+            frame.push(byte ^ ESCAPE_XOR);
+        } else {
+            frame.push(byte);
+        }
+    }
+
+    frame.push(FLAG_BYTE);
+    frame
+}
+
+/// Calculates the CRC-16-CCITT of a message, as defined by GDL90
+///
+/// # Arguments
+///
+/// * `message` - The message
+fn calculate_crc(message: &[u8]) -> u16 {
+    message.iter().fold(0, |crc, &byte| {
+        CRC_TABLE[usize::from(crc >> 8)] ^ (crc << 8) ^ u16::from(byte)
+    })
+}
+
+/// Creates the lookup table of the CRC-16-CCITT (polynomial 0x1021)
+const fn create_crc_table() -> [u16; 256] {
+    let mut table = [0; 256];
+    let mut i = 0;
+
+    while i < 256 {
+        let mut crc = (i as u16) << 8;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+
+        table[i] = crc;
+        i += 1;
+    }
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aprs::Status;
+
+    use super::*;
+
+    #[test]
+    fn creates_heartbeat() {
+        /* 1970-01-02 11:39:31 => 41971 seconds since midnight */
+        let result = create_heartbeat(86400 + 41971);
+
+        assert_eq!(
+            result,
+            [0x7E, 0x00, 0x81, 0x01, 0xF3, 0xA3, 0x00, 0x00, 0xA7, 0x5F, 0x7E]
+        );
+    }
+
+    #[test]
+    fn creates_traffic_report() {
+        let mut status = Status {
+            speed: Some(228),
+            vertical_speed: Some(0.32512),
+            altitude: Some(1524),
+            course: Some(45),
+            aircraft_type: Some(AircraftType::MotorAircraft),
+            time_stamp: 0,
+            ..Status::create_for_test("AB4549", 45.0, -90.0)
+        };
+        status.aircraft.registration = Some(String::from("N825V"));
+
+        let result = create_traffic_report(&StatusDto::from(&status, None));
+
+        /* Strip flags and CRC */
+        assert_eq!(
+            result[1..result.len() - 3],
+            [
+                0x14, 0x01, 0xAB, 0x45, 0x49, 0x20, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x0F, 0x09, 0x00,
+                0x07, 0xB0, 0x01, 0x20, 0x01, 0x4E, 0x38, 0x32, 0x35, 0x56, 0x20, 0x20, 0x20, 0x00
+            ]
+        );
+    }
+
+    #[test]
+    fn create_report_limits_altitude() {
+        let position = Position {
+            latitude: 45.0,
+            longitude: -90.0,
+        };
+
+        let get_altitude_code = |altitude: Option<u16>| {
+            let report = Report {
+                position: &position,
+                altitude,
+                speed: None,
+                vertical_speed: None,
+                course: None,
+                airborne: true,
+                emitter_category: 0,
+                call_sign: "",
+            };
+            let message = create_report(MESSAGE_ID_TRAFFIC_REPORT, 0, &report);
+
+            u16::from_be_bytes([message[11], message[12]]) >> 4
+        };
+
+        /* 5000 ft */
+        assert_eq!(get_altitude_code(Some(1524)), 0x0F0);
+        /* Way above 101350 ft, the highest altitude that can be reported */
+        assert_eq!(get_altitude_code(Some(u16::MAX)), 0xFFE);
+        assert_eq!(get_altitude_code(None), INVALID_12_BIT);
+    }
+
+    #[test]
+    fn frame_escapes_bytes() {
+        let result = frame(&[0x7E, 0x7D, 0x01]);
+
+        assert_eq!(result[..6], [0x7E, 0x7D, 0x5E, 0x7D, 0x5D, 0x01]);
+        assert_eq!(result[result.len() - 1], 0x7E);
+    }
+
+    #[test]
+    fn calculate_crc_works() {
+        /* Example of the GDL90 specification */
+        assert_eq!(
+            calculate_crc(&[0x00, 0x81, 0x41, 0xDB, 0xD0, 0x08, 0x02]),
+            0x8BB3
+        );
+    }
+}
@@ -0,0 +1,30 @@
+pub mod flarm {
+    mod sentence;
+    mod server;
+
+    pub use server::{init, Config};
+}
+pub mod gdl90 {
+    mod broadcaster;
+    mod message;
+
+    pub use broadcaster::{init, Config};
+}
+pub mod mqtt {
+    mod publisher;
+
+    pub use publisher::{init, Config};
+}
+pub mod sbs {
+    mod message;
+    mod server;
+
+    pub use server::{init, Config};
+}
+pub mod webhook {
+    mod area;
+    mod dispatcher;
+    mod sender;
+
+    pub use dispatcher::{init, Config};
+}
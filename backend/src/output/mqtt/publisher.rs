@@ -0,0 +1,466 @@
+use std::{collections::HashSet, time::Duration};
+
+use log::{debug, info, warn};
+use rumqttc::{AsyncClient, ClientError, EventLoop, MqttOptions, QoS};
+use serde::Deserialize;
+use tokio::{
+    select,
+    sync::broadcast::error::RecvError,
+    time::{interval, sleep},
+};
+
+use crate::{
+    api::{App, Change, StatusDto},
+    aprs::Status,
+    ogn::AircraftId,
+};
+
+/// Default for `Config::port`
+const DEFAULT_PORT: u16 = 1883;
+/// Default for `Config::client_id`
+const DEFAULT_CLIENT_ID: &str = "above_me";
+/// Default for `Config::topic_prefix`
+const DEFAULT_TOPIC_PREFIX: &str = "above_me";
+/// Interval of sending keep alive packets to the broker (in seconds)
+const KEEPALIVE_INTERVAL_SECONDS: u64 = 30;
+/// Interval of publishing the overview (in seconds)
+const OVERVIEW_INTERVAL_SECONDS: u64 = 10;
+/// Delay before reconnecting after the connection to the broker failed (in seconds)
+const RECONNECT_DELAY_SECONDS: u64 = 5;
+/// Number of publish requests that may be queued while the broker is not reachable
+const REQUEST_CHANNEL_CAPACITY: usize = 256;
+
+/// Configuration of the MQTT output
+#[derive(Clone, Deserialize)]
+pub struct Config {
+    /// Host of the MQTT broker, e.g. "localhost"
+    pub host: String,
+    /// Port of the MQTT broker
+    #[serde(default = "default_port")]
+    pub port: u16,
+    /// Client id that is used when connecting to the broker
+    #[serde(default = "default_client_id")]
+    pub client_id: String,
+    /// User name for authentication, if required by the broker
+    pub user_name: Option<String>,
+    /// Password for authentication, if required by the broker
+    pub password: Option<String>,
+    /// Prefix of all topics, e.g. "above_me" for "above_me/aircraft/AB1234"
+    #[serde(default = "default_topic_prefix")]
+    pub topic_prefix: String,
+}
+
+/// Publishes every stored status of the `App` to an MQTT broker. Each aircraft gets a retained
+/// message with its current status (see `StatusDto`) on _{prefix}/aircraft/{id}_, which is
+/// cleared when the status is removed. An overview of the `App` is published regularly on
+/// _{prefix}/overview_.
+///
+/// # Arguments
+///
+/// * `config` - Configuration of the output
+/// * `app` - The `App` to receive the updates from
+///
+/// # Examples
+///
+/// ```
+/// use output::mqtt;
+/// use tokio::spawn;
+///
+/// let app = App::create(Config::default(), ddb::Index::default());
+///
+/// spawn(async move {
+///     mqtt::init(config.mqtt, app).await;
+/// });
+/// ```
+pub async fn init(config: Config, app: App) {
+    let mut options = MqttOptions::new(&config.client_id, &config.host, config.port);
+    options.set_keep_alive(Duration::from_secs(KEEPALIVE_INTERVAL_SECONDS));
+
+    if let (Some(user_name), Some(password)) = (&config.user_name, &config.password) {
+        options.set_credentials(user_name, password);
+    }
+
+    let (client, event_loop) = AsyncClient::new(options, REQUEST_CHANNEL_CAPACITY);
+
+    tokio::spawn(poll(event_loop));
+
+    info!("MQTT output publishing to {}:{}", config.host, config.port);
+
+    if let Err(e) = publish(&client, &config.topic_prefix, &app).await {
+        /* Only happens if the event loop is gone */
+        warn!("MQTT output stopped: {e}");
+    }
+}
+
+/// Publishes updates and the overview of the `App` until the client fails
+///
+/// # Arguments
+///
+/// * `client` - The MQTT client
+/// * `topic_prefix` - Prefix of all topics
+/// * `app` - The `App` to receive the updates from
+async fn publish(client: &AsyncClient, topic_prefix: &str, app: &App) -> Result<(), ClientError> {
+    let mut update_rx = app.subscribe();
+    let mut overview_interval = interval(Duration::from_secs(OVERVIEW_INTERVAL_SECONDS));
+
+    /* Aircraft with a retained message, so that it can be cleared after missing updates */
+    let mut aircraft_ids = HashSet::new();
+
+    loop {
+        select! {
+            update = update_rx.recv() => {
+                let update = match update {
+                    Ok(update) => update,
+                    Err(RecvError::Lagged(count)) => {
+                        warn!(
+                            "MQTT output lagged behind, skipped {count} updates. Catching up..."
+                        );
+
+                        /* The backlog of updates is outdated by the current states */
+                        update_rx = update_rx.resubscribe();
+
+                        let messages = catch_up(topic_prefix, &mut aircraft_ids, app.get_states());
+
+                        for (topic, payload) in messages {
+                            client.publish(topic, QoS::AtLeastOnce, true, payload).await?;
+                        }
+
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return Ok(()),
+                };
+
+                let (topic, payload) = match update.change {
+                    Change::Stored(status) => {
+                        aircraft_ids.insert(status.aircraft.id.clone());
+
+                        (
+                            get_aircraft_topic(topic_prefix, &status.aircraft.id),
+                            get_aircraft_payload(&status),
+                        )
+                    }
+                    /* An empty retained message removes the retained message of the topic */
+                    Change::Lost(status) => {
+                        aircraft_ids.remove(&status.aircraft.id);
+
+                        (get_aircraft_topic(topic_prefix, &status.aircraft.id), vec![])
+                    }
+                };
+
+                client.publish(topic, QoS::AtLeastOnce, true, payload).await?;
+            },
+            _ = overview_interval.tick() => {
+                let payload = serde_json::to_vec(&app.get_overview())
+                    .expect("Overview can always be serialized");
+
+                client
+                    .publish(get_overview_topic(topic_prefix), QoS::AtLeastOnce, true, payload)
+                    .await?;
+            }
+        }
+    }
+}
+
+/// Compares the aircraft that have a retained message with the current states of the `App`, so
+/// that the retained messages are up to date again after missing updates
+///
+/// # Arguments
+///
+/// * `topic_prefix` - Prefix of all topics
+/// * `aircraft_ids` - Ids of the aircraft that have a retained message, updated in place
+/// * `states` - All current states of the `App`
+///
+/// # Returns
+///
+/// Topics and payloads of the messages to publish: an empty one for every aircraft that is
+/// gone, the current status for every other aircraft
+///
+/// # Examples
+///
+/// * test `publisher::catch_up_clears_lost_aircraft`
+fn catch_up(
+    topic_prefix: &str,
+    aircraft_ids: &mut HashSet<AircraftId>,
+    states: Vec<Status>,
+) -> Vec<(String, Vec<u8>)> {
+    let current_ids = states
+        .iter()
+        .map(|status| status.aircraft.id.clone())
+        .collect::<HashSet<AircraftId>>();
+
+    let mut messages = aircraft_ids
+        .difference(&current_ids)
+        .map(|id| (get_aircraft_topic(topic_prefix, id), vec![]))
+        .collect::<Vec<(String, Vec<u8>)>>();
+
+    messages.extend(states.iter().map(|status| {
+        (
+            get_aircraft_topic(topic_prefix, &status.aircraft.id),
+            get_aircraft_payload(status),
+        )
+    }));
+
+    *aircraft_ids = current_ids;
+
+    messages
+}
+
+/// Drives the connection to the broker, reconnecting whenever it fails
+///
+/// # Arguments
+///
+/// * `event_loop` - The `EventLoop` of the MQTT client
+async fn poll(mut event_loop: EventLoop) {
+    loop {
+        match event_loop.poll().await {
+            Ok(event) => debug!("MQTT event: {event:?}"),
+            Err(e) => {
+                warn!("MQTT connection failed: {e}. Reconnecting...");
+                sleep(Duration::from_secs(RECONNECT_DELAY_SECONDS)).await;
+            }
+        }
+    }
+}
+
+/// Returns the topic of an aircraft
+///
+/// # Arguments
+///
+/// * `topic_prefix` - Prefix of all topics
+/// * `id` - Id of the aircraft
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(get_aircraft_topic("above_me", "AB1234"), "above_me/aircraft/AB1234");
+/// ```
+fn get_aircraft_topic(topic_prefix: &str, id: &str) -> String {
+    format!("{topic_prefix}/aircraft/{id}")
+}
+
+/// Returns the payload of the retained message of an aircraft. This is the `StatusDto` of the
+/// status without `age`, as the age would be outdated as soon as the message is retained.
+///
+/// # Arguments
+///
+/// * `status` - The status of the aircraft
+///
+/// # Examples
+///
+/// * test `publisher::aircraft_payload_has_no_age`
+fn get_aircraft_payload(status: &Status) -> Vec<u8> {
+    let mut status_dto = serde_json::to_value(StatusDto::from(status, None))
+        .expect("Status can always be serialized");
+
+    if let Some(fields) = status_dto.as_object_mut() {
+        fields.remove("age");
+    }
+
+    serde_json::to_vec(&status_dto).expect("Status can always be serialized")
+}
+
+/// Returns the topic of the overview
+///
+/// # Arguments
+///
+/// * `topic_prefix` - Prefix of all topics
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(get_overview_topic("above_me"), "above_me/overview");
+/// ```
+fn get_overview_topic(topic_prefix: &str) -> String {
+    format!("{topic_prefix}/overview")
+}
+
+fn default_port() -> u16 {
+    DEFAULT_PORT
+}
+
+fn default_client_id() -> String {
+    String::from(DEFAULT_CLIENT_ID)
+}
+
+fn default_topic_prefix() -> String {
+    String::from(DEFAULT_TOPIC_PREFIX)
+}
+
+#[cfg(test)]
+mod tests {
+    use rumqttc::{Event, Packet, Publish, Request};
+    use tokio::time::timeout;
+
+    use crate::{api, ogn::ddb, time::get_current_timestamp};
+
+    use super::*;
+
+    #[test]
+    fn topics_are_created() {
+        assert_eq!(
+            get_aircraft_topic("above_me", "AB1234"),
+            "above_me/aircraft/AB1234"
+        );
+        assert_eq!(get_overview_topic("club/traffic"), "club/traffic/overview");
+    }
+
+    #[test]
+    fn aircraft_payload_has_no_age() {
+        let payload: serde_json::Value =
+            serde_json::from_slice(&get_aircraft_payload(&create_status())).unwrap();
+
+        assert_eq!(payload["aircraft"]["registration"], "D-6507");
+        assert_eq!(payload["altitude"], 1000);
+        assert!(payload["time_stamp"].is_u64());
+        assert!(payload.get("age").is_none());
+    }
+
+    #[test]
+    fn catch_up_clears_lost_aircraft() {
+        let mut aircraft_ids = HashSet::from([String::from("AB1234"), String::from("CD5678")]);
+
+        let mut status = create_status();
+        status.aircraft.id = String::from("EF9012");
+
+        let result = catch_up("above_me", &mut aircraft_ids, vec![create_status(), status]);
+
+        assert_eq!(result.len(), 3);
+        assert!(result
+            .iter()
+            .any(|(topic, payload)| topic == "above_me/aircraft/CD5678" && payload.is_empty()));
+        assert!(result
+            .iter()
+            .any(|(topic, payload)| topic == "above_me/aircraft/AB1234" && !payload.is_empty()));
+        assert!(result
+            .iter()
+            .any(|(topic, payload)| topic == "above_me/aircraft/EF9012" && !payload.is_empty()));
+        assert_eq!(
+            aircraft_ids,
+            HashSet::from([String::from("AB1234"), String::from("EF9012")])
+        );
+    }
+
+    #[tokio::test]
+    async fn publish_retains_states() {
+        let app = App::create(api::Config::default(), ddb::Index::default());
+
+        /* The event loop is never polled, so the client doesn't connect */
+        let (client, mut event_loop) = AsyncClient::new(
+            MqttOptions::new("above_me_test_publisher", "localhost", DEFAULT_PORT),
+            10,
+        );
+
+        let publisher = tokio::spawn({
+            let app = app.clone();
+            async move { publish(&client, "above_me", &app).await }
+        });
+
+        /* Overview is published right away, after subscribing to the updates */
+        let overview = get_next_publish(&mut event_loop).await;
+        assert_eq!(overview.topic, "above_me/overview");
+
+        let mut status = create_status();
+        status.time_stamp -= 60 * 60;
+
+        app.push_status(status);
+        app.remove_outdated_states();
+
+        let stored = get_next_publish(&mut event_loop).await;
+        let payload: serde_json::Value = serde_json::from_slice(&stored.payload).unwrap();
+
+        assert_eq!(stored.topic, "above_me/aircraft/AB1234");
+        assert!(stored.retain);
+        assert_eq!(payload["aircraft"]["registration"], "D-6507");
+        assert_eq!(payload["altitude"], 1000);
+
+        let lost = get_next_publish(&mut event_loop).await;
+
+        assert_eq!(lost.topic, "above_me/aircraft/AB1234");
+        assert!(lost.retain);
+        assert!(lost.payload.is_empty());
+
+        publisher.abort();
+    }
+
+    /// Requires an MQTT broker on localhost:1883, e.g. `docker run -p 1883:1883 eclipse-mosquitto
+    /// mosquitto -c /mosquitto-no-auth.conf`. Run with `cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore]
+    async fn publishes_status() {
+        let app = App::create(api::Config::default(), ddb::Index::default());
+        let topic_prefix = format!("above_me_test_{}", get_current_timestamp());
+
+        tokio::spawn(init(
+            Config {
+                host: String::from("localhost"),
+                port: DEFAULT_PORT,
+                client_id: String::from("above_me_test_publisher"),
+                user_name: None,
+                password: None,
+                topic_prefix: topic_prefix.clone(),
+            },
+            app.clone(),
+        ));
+
+        let (client, mut event_loop) = AsyncClient::new(
+            MqttOptions::new("above_me_test_subscriber", "localhost", DEFAULT_PORT),
+            10,
+        );
+        client
+            .subscribe(
+                get_aircraft_topic(&topic_prefix, "AB1234"),
+                QoS::AtLeastOnce,
+            )
+            .await
+            .unwrap();
+
+        /* Wait until the publisher is connected and subscribed to the updates */
+        sleep(Duration::from_secs(1)).await;
+
+        app.push_status(create_status());
+
+        let publish = timeout(Duration::from_secs(5), async {
+            loop {
+                if let Event::Incoming(Packet::Publish(publish)) = event_loop.poll().await.unwrap()
+                {
+                    return publish;
+                }
+            }
+        })
+        .await
+        .expect("No message received");
+
+        let payload: serde_json::Value = serde_json::from_slice(&publish.payload).unwrap();
+        assert!(publish.retain);
+        assert_eq!(payload["aircraft"]["registration"], "D-6507");
+        assert_eq!(payload["altitude"], 1000);
+    }
+
+    /// Waits for the next message that the client hands to the event loop for publishing
+    async fn get_next_publish(event_loop: &mut EventLoop) -> Publish {
+        timeout(Duration::from_secs(5), async {
+            loop {
+                /* Moves the requests of the client to the pending ones */
+                event_loop.clean();
+
+                match event_loop.pending.pop_front() {
+                    Some(Request::Publish(publish)) => return publish,
+                    Some(_) => continue,
+                    None => sleep(Duration::from_millis(10)).await,
+                }
+            }
+        })
+        .await
+        .expect("No message published")
+    }
+
+    fn create_status() -> Status {
+        let mut status = Status {
+            altitude: Some(1000),
+            ..Status::create_for_test("AB1234", 48.5, 2.25)
+        };
+        status.aircraft.call_sign = Some(String::from("G1"));
+        status.aircraft.registration = Some(String::from("D-6507"));
+        status
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    aprs::Status,
+    time::{get_date_time, DateTime},
+};
+
+/// Factor to convert _m_ to _ft_
+const FACTOR_M_TO_FT: f32 = 1.0 / 0.3048;
+/// Factor to convert _km/h_ to _knots_
+const FACTOR_KM_H_TO_KNOTS: f32 = 1.0 / 1.852;
+/// Factor to convert _m/s_ to _ft/min_
+const FACTOR_M_S_TO_FT_MIN: f32 = 60.0 / 0.3048;
+
+/// Creates the SBS-1 (BaseStation) messages of a status: _MSG,3_ (airborne position) and
+/// _MSG,4_ (airborne velocity). The aircraft id is used as hex id and the registration or,
+/// if unknown, the call sign as callsign.
+///
+/// # Arguments
+///
+/// * `status` - The status to convert
+///
+/// # Examples
+///
+/// * test `message::creates_messages`
+///
+/// # Resources
+///
+/// * [BaseStation format](http://woodair.net/sbs/article/barebones42_socket_data.htm)
+pub fn create_messages(status: &Status) -> [String; 2] {
+    let date_time = get_date_time(status.time_stamp);
+
+    let call_sign = status
+        .aircraft
+        .registration
+        .as_ref()
+        .or(status.aircraft.call_sign.as_ref())
+        .map(|c| c.replace(',', ""))
+        .unwrap_or_default();
+
+    let altitude = status
+        .altitude
+        .map(|a| format!("{:.0}", f32::from(a) * FACTOR_M_TO_FT))
+        .unwrap_or_default();
+
+    let ground_speed = status
+        .speed
+        .map(|s| format!("{:.0}", f32::from(s) * FACTOR_KM_H_TO_KNOTS))
+        .unwrap_or_default();
+
+    let track = status.course.map(|c| c.to_string()).unwrap_or_default();
+
+    let vertical_rate = status
+        .vertical_speed
+        .map(|v| format!("{:.0}", v * FACTOR_M_S_TO_FT_MIN))
+        .unwrap_or_default();
+
+    [
+        create_message(
+            3,
+            &status.aircraft.id,
+            &date_time,
+            &format!(
+                "{call_sign},{altitude},,,{:.5},{:.5},,",
+                status.position.latitude, status.position.longitude
+            ),
+        ),
+        create_message(
+            4,
+            &status.aircraft.id,
+            &date_time,
+            &format!("{call_sign},,{ground_speed},{track},,,{vertical_rate},"),
+        ),
+    ]
+}
+
+/// Creates a single SBS-1 message line
+///
+/// # Arguments
+///
+/// * `transmission_type` - Type of the message, e.g. 3 for airborne position
+/// * `id` - Hex id of the aircraft
+/// * `date_time` - Date and time that the message was generated
+/// * `fields` - Fields 11 (callsign) to 18 (squawk), separated by commas
+fn create_message(transmission_type: u8, id: &str, date_time: &DateTime, fields: &str) -> String {
+    let date = format!(
+        "{:04}/{:02}/{:02}",
+        date_time.year, date_time.month, date_time.day
+    );
+    let time = format!(
+        "{:02}:{:02}:{:02}.000",
+        date_time.hour, date_time.minute, date_time.second
+    );
+
+    /* Alert, emergency, SPI and on-ground flags are not known */
+    format!("MSG,{transmission_type},1,1,{id},1,{date},{time},{date},{time},{fields},0,0,0,0\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn creates_messages() {
+        let mut status = Status {
+            speed: Some(100),
+            vertical_speed: Some(-1.5),
+            altitude: Some(1000),
+            course: Some(90),
+            time_stamp: 1_709_210_096,
+            ..Status::create_for_test("AB1234", 48.5, -2.25)
+        };
+        status.aircraft.call_sign = Some(String::from("G1"));
+        status.aircraft.registration = Some(String::from("D-6507"));
+
+        let [position, velocity] = create_messages(&status);
+
+        assert_eq!(
+            position,
+            "MSG,3,1,1,AB1234,1,2024/02/29,12:34:56.000,2024/02/29,12:34:56.000,D-6507,3281,,,48.50000,-2.25000,,,0,0,0,0\r\n"
+        );
+        assert_eq!(
+            velocity,
+            "MSG,4,1,1,AB1234,1,2024/02/29,12:34:56.000,2024/02/29,12:34:56.000,D-6507,,54,90,,,-295,,0,0,0,0\r\n"
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use std::{io::Error, net::SocketAddr};
+
+use log::{debug, info, warn};
+use serde::Deserialize;
+use tokio::{
+    io::AsyncWriteExt,
+    net::{TcpListener, TcpStream},
+    sync::broadcast::error::RecvError,
+};
+
+use crate::api::{App, Change, Update};
+
+use super::message::create_messages;
+
+/// Configuration of the SBS-1 output
+#[derive(Clone, Deserialize)]
+pub struct Config {
+    /// Address that the TCP server should bind to, e.g. "0.0.0.0:30003"
+    pub bind_to: String,
+}
+
+/// Initializes a TCP server that sends every incoming status as SBS-1 (BaseStation) messages
+/// to every connected client, e.g. Virtual Radar Server or tar1090.
+///
+/// # Arguments
+///
+/// * `config` - Configuration of the output
+/// * `app` - The `App` to receive the updates from
+///
+/// # Returns
+///
+/// Future that will only result to Error, if the server could not be started.
+///
+/// # Examples
+///
+/// ```
+/// use output::sbs;
+/// use tokio::spawn;
+///
+/// let app = App::create(Config::default(), ddb::Index::default());
+///
+/// spawn(async move {
+///     sbs::init(config.sbs, app)
+///         .await
+///         .expect("SBS-1 output failed");
+/// });
+/// ```
+pub async fn init(config: Config, app: App) -> Result<(), Error> {
+    let listener = TcpListener::bind(&config.bind_to).await?;
+
+    info!("SBS-1 output listening on {}", config.bind_to);
+
+    loop {
+        let (stream, address) = listener.accept().await?;
+
+        debug!("SBS-1 client {address} connected");
+
+        tokio::spawn(handle(stream, address, app.clone()));
+    }
+}
+
+/// Sends every stored status to a connected client, until it disconnects. If the client is too
+/// slow and updates are missed, all current states are sent instead.
+///
+/// # Arguments
+///
+/// * `stream` - The `TcpStream` of the client
+/// * `address` - Address of the client
+/// * `app` - The `App` to receive the updates from
+async fn handle(mut stream: TcpStream, address: SocketAddr, app: App) {
+    let mut update_rx = app.subscribe();
+
+    loop {
+        let states = match update_rx.recv().await {
+            Ok(Update {
+                change: Change::Stored(status),
+                ..
+            }) => vec![status],
+            /* SBS-1 has no message for aircraft that are gone, clients time them out */
+            Ok(_) => continue,
+            Err(RecvError::Lagged(count)) => {
+                warn!(
+                    "SBS-1 client {address} lagged behind, skipped {count} updates. Catching up..."
+                );
+
+                /* The backlog of updates is outdated by the current states */
+                update_rx = update_rx.resubscribe();
+                app.get_states()
+            }
+            Err(RecvError::Closed) => return,
+        };
+
+        let messages = states
+            .iter()
+            .map(|status| create_messages(status).concat())
+            .collect::<String>();
+
+        if let Err(e) = stream.write_all(messages.as_bytes()).await {
+            debug!("SBS-1 client {address} disconnected: {e}");
+            return;
+        }
+    }
+}
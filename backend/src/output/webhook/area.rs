@@ -0,0 +1,86 @@
+use serde::Deserialize;
+
+use crate::position::{calculate_distance, Polygon, Position};
+
+/// Area that a webhook watches
+#[derive(Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Area {
+    /// Circle around a position
+    Radius {
+        /// Center of the area
+        position: Position,
+        /// Range around `position` in _km_
+        range: f32,
+    },
+    /// Polygon of positions
+    Polygon {
+        /// Corners of the polygon, in order
+        corners: Polygon,
+    },
+}
+
+impl Area {
+    /// Checks whether a position is within the `Area`
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The position to check
+    ///
+    /// # Examples
+    ///
+    /// * test `area::contains_works`
+    pub fn contains(&self, position: &Position) -> bool {
+        match self {
+            Self::Radius {
+                position: center,
+                range,
+            } => calculate_distance(center, position) <= *range,
+            Self::Polygon { corners } => corners.contains(position),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn contains_works() {
+        let radius = Area::Radius {
+            position: Position {
+                latitude: 48.858222,
+                longitude: 2.2945,
+            },
+            range: 5.0,
+        };
+
+        let polygon: Area = serde_json::from_str(
+            r#"{
+                "type": "polygon",
+                "corners": [
+                    { "latitude": 48.0, "longitude": 2.0 },
+                    { "latitude": 48.0, "longitude": 3.0 },
+                    { "latitude": 49.0, "longitude": 3.0 },
+                    { "latitude": 49.0, "longitude": 2.0 }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let position = Position {
+            latitude: 48.86055,
+            longitude: 2.3376,
+        };
+
+        let other_position = Position {
+            latitude: 50.0,
+            longitude: 2.3376,
+        };
+
+        assert!(radius.contains(&position));
+        assert!(!radius.contains(&other_position));
+        assert!(polygon.contains(&position));
+        assert!(!polygon.contains(&other_position));
+    }
+}
@@ -0,0 +1,381 @@
+use std::collections::{HashMap, HashSet};
+
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{
+    broadcast::error::RecvError,
+    mpsc::{self, error::TrySendError},
+};
+
+use crate::{
+    api::{App, Change},
+    aprs::Status,
+    ogn::{Aircraft, AircraftId},
+    position::Position,
+};
+
+use super::{
+    area::Area,
+    sender::{send, Delivery},
+};
+
+/// Number of events that may be queued per webhook while previous ones are being delivered
+const DELIVERY_CHANNEL_CAPACITY: usize = 64;
+
+/// Configuration of a webhook
+#[derive(Clone, Deserialize)]
+pub struct Config {
+    /// Url that events are POSTed to
+    pub url: String,
+    /// Secret that the events are signed with (HMAC-SHA256), if given
+    pub secret: Option<String>,
+    /// Area that is watched
+    pub area: Area,
+}
+
+/// Aircraft entered or left the area of a webhook
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event<'a> {
+    /// Aircraft entered the area
+    Entered {
+        id: &'a AircraftId,
+        aircraft: &'a Aircraft,
+        position: &'a Position,
+        time_stamp: u64,
+    },
+    /// Aircraft left the area
+    Left {
+        id: &'a AircraftId,
+        aircraft: &'a Aircraft,
+        position: &'a Position,
+        time_stamp: u64,
+    },
+    /// Aircraft within the area has been lost as its status is outdated. Contains the last
+    /// known position.
+    Lost {
+        id: &'a AircraftId,
+        aircraft: &'a Aircraft,
+        position: &'a Position,
+        time_stamp: u64,
+    },
+}
+
+/// Tracks which aircraft are within an area
+struct Tracker {
+    /// The watched area
+    area: Area,
+    /// Latest states of the aircraft that are currently within the area
+    states: HashMap<AircraftId, Status>,
+}
+
+/// Watches the area of a webhook and POSTs an event to its url whenever an aircraft enters or
+/// leaves that area.
+///
+/// # Arguments
+///
+/// * `config` - Configuration of the webhook
+/// * `app` - The `App` to receive the updates from
+///
+/// # Examples
+///
+/// ```
+/// use output::webhook;
+/// use tokio::spawn;
+///
+/// let app = App::create(Config::default(), ddb::Index::default());
+///
+/// for webhook_config in config.webhooks {
+///     spawn(webhook::init(webhook_config, app.clone()));
+/// }
+/// ```
+pub async fn init(config: Config, app: App) {
+    let mut update_rx = app.subscribe();
+    let (delivery_tx, delivery_rx) = mpsc::channel(DELIVERY_CHANNEL_CAPACITY);
+
+    tokio::spawn(send(config.url.clone(), config.secret, delivery_rx));
+
+    info!("Webhook {} watching its area", config.url);
+
+    let mut tracker = Tracker {
+        area: config.area,
+        states: HashMap::new(),
+    };
+
+    loop {
+        let update = match update_rx.recv().await {
+            Ok(update) => update,
+            Err(RecvError::Lagged(count)) => {
+                warn!(
+                    "Webhook {} lagged behind, skipped {count} updates. Catching up...",
+                    config.url
+                );
+
+                /* The backlog of updates is outdated by the current states */
+                update_rx = update_rx.resubscribe();
+
+                for delivery in tracker.catch_up(app.get_states()) {
+                    deliver(&delivery_tx, &config.url, delivery);
+                }
+
+                continue;
+            }
+            Err(RecvError::Closed) => return,
+        };
+
+        if let Some(delivery) = tracker.handle_change(update.change) {
+            deliver(&delivery_tx, &config.url, delivery);
+        }
+    }
+}
+
+/// Queues a `Delivery` for sending, dropping it if the webhook is too slow
+///
+/// # Arguments
+///
+/// * `delivery_tx` - Sender of the deliveries of the webhook
+/// * `url` - Url of the webhook
+/// * `delivery` - The `Delivery` to queue
+fn deliver(delivery_tx: &mpsc::Sender<Delivery>, url: &str, delivery: Delivery) {
+    if let Err(TrySendError::Full(delivery)) = delivery_tx.try_send(delivery) {
+        warn!("Webhook {url} is too slow, dropped {} event", delivery.name);
+    }
+}
+
+impl Tracker {
+    /// Updates the aircraft within the area by a `Change` of the `App`
+    ///
+    /// # Arguments
+    ///
+    /// * `change` - The `Change` to handle
+    ///
+    /// # Returns
+    ///
+    /// A `Delivery` of the resulting `Event`, if the aircraft entered or left the area
+    ///
+    /// # Examples
+    ///
+    /// * test `dispatcher::handle_change_works`
+    fn handle_change(&mut self, change: Change) -> Option<Delivery> {
+        match change {
+            Change::Stored(status) => {
+                let is_inside = self.area.contains(&status.position);
+                let was_inside = self.states.contains_key(&status.aircraft.id);
+
+                match (was_inside, is_inside) {
+                    (false, true) => {
+                        let delivery = create_delivery(&Event::Entered {
+                            id: &status.aircraft.id,
+                            aircraft: &status.aircraft,
+                            position: &status.position,
+                            time_stamp: status.time_stamp,
+                        });
+
+                        self.states.insert(status.aircraft.id.clone(), status);
+                        Some(delivery)
+                    }
+                    (true, true) => {
+                        self.states.insert(status.aircraft.id.clone(), status);
+                        None
+                    }
+                    (true, false) => {
+                        self.states.remove(&status.aircraft.id);
+
+                        Some(create_delivery(&Event::Left {
+                            id: &status.aircraft.id,
+                            aircraft: &status.aircraft,
+                            position: &status.position,
+                            time_stamp: status.time_stamp,
+                        }))
+                    }
+                    (false, false) => None,
+                }
+            }
+            Change::Lost(status) => {
+                self.states.remove(&status.aircraft.id)?;
+
+                Some(create_delivery(&Event::Lost {
+                    id: &status.aircraft.id,
+                    aircraft: &status.aircraft,
+                    position: &status.position,
+                    time_stamp: status.time_stamp,
+                }))
+            }
+        }
+    }
+
+    /// Compares the aircraft that are known to be within the area with the current states of
+    /// the `App`, so that the tracker is up to date again after missing updates
+    ///
+    /// # Arguments
+    ///
+    /// * `states` - All current states of the `App`
+    ///
+    /// # Returns
+    ///
+    /// Deliveries of the `Event::Entered`, `Event::Left` and `Event::Lost` events that have
+    /// been missed. Aircraft that are not current anymore are reported as lost.
+    ///
+    /// # Examples
+    ///
+    /// * test `dispatcher::catch_up_works`
+    fn catch_up(&mut self, states: Vec<Status>) -> Vec<Delivery> {
+        let current_ids = states
+            .iter()
+            .map(|status| status.aircraft.id.clone())
+            .collect::<HashSet<AircraftId>>();
+
+        let mut lost_states = self.states.clone();
+        lost_states.retain(|id, _| !current_ids.contains(id));
+
+        let mut deliveries = states
+            .into_iter()
+            .filter_map(|status| self.handle_change(Change::Stored(status)))
+            .collect::<Vec<Delivery>>();
+
+        deliveries.extend(
+            lost_states
+                .into_values()
+                .filter_map(|status| self.handle_change(Change::Lost(status))),
+        );
+
+        deliveries
+    }
+}
+
+/// Creates the `Delivery` of an `Event`
+///
+/// # Arguments
+///
+/// * `event` - The `Event` to deliver
+fn create_delivery(event: &Event) -> Delivery {
+    Delivery {
+        name: match event {
+            Event::Entered { .. } => "entered",
+            Event::Left { .. } => "left",
+            Event::Lost { .. } => "lost",
+        },
+        body: serde_json::to_vec(event).expect("Event can always be serialized"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn handle_change_works() {
+        let mut sut = Tracker {
+            area: Area::Radius {
+                position: Position {
+                    latitude: 48.858222,
+                    longitude: 2.2945,
+                },
+                range: 5.0,
+            },
+            states: HashMap::new(),
+        };
+
+        /* Outside */
+        assert!(sut
+            .handle_change(Change::Stored(create_status(50.0, 1)))
+            .is_none());
+
+        let result = sut
+            .handle_change(Change::Stored(create_status(48.86055, 2)))
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&result.body).unwrap();
+        assert_eq!(result.name, "entered");
+        assert_eq!(body["type"], "entered");
+        assert_eq!(body["id"], "AB1234");
+        assert_eq!(body["aircraft"]["registration"], "D-6507");
+        assert_eq!(body["time_stamp"], 2);
+
+        /* Still inside */
+        assert!(sut
+            .handle_change(Change::Stored(create_status(48.87, 3)))
+            .is_none());
+
+        let result = sut
+            .handle_change(Change::Stored(create_status(50.0, 4)))
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&result.body).unwrap();
+        assert_eq!(result.name, "left");
+        assert_eq!(body["position"]["latitude"], 50.0);
+
+        sut.handle_change(Change::Stored(create_status(48.86055, 5)));
+
+        /* Outdated status is lost with last known position */
+        let result = sut
+            .handle_change(Change::Lost(create_status(48.86055, 5)))
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&result.body).unwrap();
+        assert_eq!(result.name, "lost");
+        assert_eq!(body["type"], "lost");
+        assert_eq!(body["time_stamp"], 5);
+
+        assert!(sut
+            .handle_change(Change::Lost(create_status(48.86055, 5)))
+            .is_none());
+    }
+
+    #[test]
+    fn catch_up_works() {
+        let mut sut = Tracker {
+            area: Area::Radius {
+                position: Position {
+                    latitude: 48.858222,
+                    longitude: 2.2945,
+                },
+                range: 5.0,
+            },
+            states: HashMap::new(),
+        };
+
+        sut.handle_change(Change::Stored(create_status_of("AB1234", 48.86055, 1)));
+        sut.handle_change(Change::Stored(create_status_of("CD5678", 48.86055, 1)));
+        sut.handle_change(Change::Stored(create_status_of("EF9012", 48.86055, 1)));
+
+        /* AB1234 left, CD5678 is lost, EF9012 is still inside and GH3456 entered */
+        let result = sut.catch_up(vec![
+            create_status_of("AB1234", 50.0, 2),
+            create_status_of("EF9012", 48.87, 2),
+            create_status_of("GH3456", 48.86055, 2),
+            create_status_of("IJ7890", 50.0, 2),
+        ]);
+
+        let mut events = result
+            .iter()
+            .map(|delivery| {
+                let body: serde_json::Value = serde_json::from_slice(&delivery.body).unwrap();
+                (delivery.name, body["id"].as_str().unwrap().to_owned())
+            })
+            .collect::<Vec<(&str, String)>>();
+        events.sort();
+
+        assert_eq!(
+            events,
+            vec![
+                ("entered", String::from("GH3456")),
+                ("left", String::from("AB1234")),
+                ("lost", String::from("CD5678"))
+            ]
+        );
+        assert_eq!(sut.states.len(), 2);
+        assert_eq!(sut.states["EF9012"].time_stamp, 2);
+    }
+
+    fn create_status_of(aircraft_id: &str, latitude: f32, time_stamp: u64) -> Status {
+        let mut status = Status {
+            time_stamp,
+            ..Status::create_for_test(aircraft_id, latitude, 2.3376)
+        };
+        status.aircraft.call_sign = Some(String::from("G1"));
+        status.aircraft.registration = Some(String::from("D-6507"));
+        status
+    }
+
+    fn create_status(latitude: f32, time_stamp: u64) -> Status {
+        create_status_of("AB1234", latitude, time_stamp)
+    }
+}
@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use log::{debug, warn};
+use reqwest::{header, Client, StatusCode};
+use sha2::Sha256;
+use tokio::{sync::mpsc::Receiver, time::sleep};
+
+use crate::time::get_current_timestamp;
+
+/// Header that contains the HMAC-SHA256 signature of the timestamp and the body
+const HEADER_SIGNATURE: &str = "x-above-me-signature";
+/// Header that contains the Unix timestamp of sending the request
+const HEADER_TIMESTAMP: &str = "x-above-me-timestamp";
+/// Header that contains the name of the event
+const HEADER_EVENT: &str = "x-above-me-event";
+/// Number of attempts to deliver an event
+const MAX_ATTEMPTS: u32 = 4;
+/// Delay before the first retry (in seconds), doubled with every further retry
+const RETRY_DELAY_SECONDS: u64 = 1;
+/// Timeout of a single request (in seconds)
+const REQUEST_TIMEOUT_SECONDS: u64 = 10;
+
+/// An event that should be delivered to a webhook
+pub struct Delivery {
+    /// Name of the event
+    pub name: &'static str,
+    /// JSON body of the request
+    pub body: Vec<u8>,
+}
+
+/// Delivers events to a webhook url, one after another so that their order is kept. Failed
+/// deliveries are retried with increasing delay.
+///
+/// # Arguments
+///
+/// * `url` - Url of the webhook
+/// * `secret` - Secret that the timestamp and the body are signed with, if given
+/// * `delivery_rx` - Receiver of the events to deliver
+pub async fn send(url: String, secret: Option<String>, mut delivery_rx: Receiver<Delivery>) {
+    let client = Client::builder()
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECONDS))
+        .build()
+        .expect("HTTP client can always be built");
+
+    while let Some(delivery) = delivery_rx.recv().await {
+        for attempt in 1..=MAX_ATTEMPTS {
+            /* Receivers reject stale timestamps, so that captured requests can't be replayed.
+             * Therefore every attempt is signed anew. */
+            let timestamp = get_current_timestamp();
+
+            let mut request = client
+                .post(&url)
+                .header(header::CONTENT_TYPE, "application/json")
+                .header(HEADER_EVENT, delivery.name)
+                .header(HEADER_TIMESTAMP, timestamp)
+                .body(delivery.body.clone());
+
+            if let Some(secret) = &secret {
+                let signature = sign(secret, timestamp, &delivery.body);
+                request = request.header(HEADER_SIGNATURE, format!("sha256={signature}"));
+            }
+
+            match request.send().await {
+                Ok(response) if response.status().is_success() => {
+                    debug!("Delivered {} event to webhook {url}", delivery.name);
+                    break;
+                }
+                /* Client errors won't go away by retrying, except for rate limiting */
+                Ok(response)
+                    if response.status().is_client_error()
+                        && response.status() != StatusCode::TOO_MANY_REQUESTS =>
+                {
+                    warn!(
+                        "Webhook {url} rejected {} event with status {}",
+                        delivery.name,
+                        response.status()
+                    );
+                    break;
+                }
+                Ok(response) => warn!(
+                    "Webhook {url} responded with status {} (attempt {attempt} of {MAX_ATTEMPTS})",
+                    response.status()
+                ),
+                Err(e) => warn!(
+                    "Could not reach webhook {url}: {e} (attempt {attempt} of {MAX_ATTEMPTS})"
+                ),
+            }
+
+            if attempt < MAX_ATTEMPTS {
+                sleep(Duration::from_secs(RETRY_DELAY_SECONDS << (attempt - 1))).await;
+            }
+        }
+    }
+}
+
+/// Signs a body with HMAC-SHA256. The signed message is "{timestamp}.{body}", so that the
+/// timestamp can't be changed without breaking the signature.
+///
+/// # Arguments
+///
+/// * `secret` - The secret key
+/// * `timestamp` - Unix timestamp of sending the body
+/// * `body` - The body to sign
+///
+/// # Returns
+///
+/// The signature as lowercase hex string
+///
+/// # Examples
+///
+/// * test `sender::sign_works`
+fn sign(secret: &str, timestamp: u64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(format!("{timestamp}.").as_bytes());
+    mac.update(body);
+
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_works() {
+        /* Test case 2 of RFC 4231, with the timestamp prepended to the data */
+        assert_eq!(
+            sign("Jefe", 1_700_000_000, b"what do ya want for nothing?"),
+            "1cdd0650c8be1cb0974b1788d458b1e781206cfef59b85faafc582d2e182c57e"
+        );
+    }
+}
@@ -1,11 +1,16 @@
 use std::fmt::{Display, Formatter, Result};
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 const EARTH_MEAN_RADIUS_KM: f32 = 6371.0;
+/// Points of the compass, clockwise from north
+const COMPASS_POINTS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW",
+    "NNW",
+];
 
 /// Representation of a position
-#[derive(Clone, Serialize)]
+#[derive(Clone, Deserialize, Serialize)]
 pub struct Position {
     /// Latitude
     pub latitude: f32,
@@ -57,6 +62,412 @@ pub fn calculate_distance(pos1: &Position, pos2: &Position) -> f32 {
     c * EARTH_MEAN_RADIUS_KM
 }
 
+/// Calculates the offset of a position relative to an origin in km, northwards and eastwards.
+/// Uses an equirectangular approximation, which is precise enough for short distances.
+///
+/// # Arguments
+///
+/// * `origin` - The position that the offset is relative to
+/// * `position` - The position to calculate the offset of
+///
+/// # Returns
+///
+/// Tuple of _(north, east)_ in km. Negative values mean south or west, respectively.
+///
+/// # Examples
+///
+/// * test `position::calculates_correct_offset`
+///
+/// # Resources
+///
+/// * [www.movable-type.co.uk](https://www.movable-type.co.uk/scripts/latlong.html)
+pub fn calculate_offset(origin: &Position, position: &Position) -> (f32, f32) {
+    let mut delta_longitude = position.longitude - origin.longitude;
+
+    /* Take the short way across the antimeridian */
+    if delta_longitude > 180.0 {
+        delta_longitude -= 360.0;
+    } else if delta_longitude < -180.0 {
+        delta_longitude += 360.0;
+    }
+
+    let mean_latitude = ((origin.latitude + position.latitude) / 2.0).to_radians();
+
+    let north = (position.latitude - origin.latitude).to_radians() * EARTH_MEAN_RADIUS_KM;
+    let east = delta_longitude.to_radians() * mean_latitude.cos() * EARTH_MEAN_RADIUS_KM;
+
+    (north, east)
+}
+
+/// Calculates the position at an offset relative to an origin, northwards and eastwards. Inverse
+/// of `calculate_offset`, also using an equirectangular approximation.
+///
+/// # Arguments
+///
+/// * `origin` - The position that the offset is relative to
+/// * `north` - Offset northwards in km, negative for southwards
+/// * `east` - Offset eastwards in km, negative for westwards
+///
+/// # Examples
+///
+/// * test `position::calculates_correct_position_by_offset`
+pub fn calculate_position_by_offset(origin: &Position, north: f32, east: f32) -> Position {
+    let latitude =
+        (origin.latitude + (north / EARTH_MEAN_RADIUS_KM).to_degrees()).clamp(-90.0, 90.0);
+    let mean_latitude = ((origin.latitude + latitude) / 2.0).to_radians();
+
+    let delta_longitude = (east / (EARTH_MEAN_RADIUS_KM * mean_latitude.cos())).to_degrees();
+
+    Position {
+        latitude,
+        longitude: normalize_longitude(origin.longitude + delta_longitude),
+    }
+}
+
+/// Calculates the initial bearing (forward azimuth) of the great circle from one position to
+/// another in degrees, clockwise from north.
+///
+/// # Arguments
+///
+/// * `from` - The position to look from
+/// * `to` - The position to look at
+///
+/// # Returns
+///
+/// Bearing in degrees, from 0 (inclusive) to 360 (exclusive)
+///
+/// # Examples
+///
+/// * test `position::calculates_correct_bearing`
+///
+/// # Resources
+///
+/// * [www.movable-type.co.uk](https://www.movable-type.co.uk/scripts/latlong.html)
+pub fn calculate_bearing(from: &Position, to: &Position) -> f32 {
+    let latitude_1 = from.latitude.to_radians();
+    let latitude_2 = to.latitude.to_radians();
+    let delta_longitude = (to.longitude - from.longitude).to_radians();
+
+    let y = delta_longitude.sin() * latitude_2.cos();
+    let x = latitude_1.cos() * latitude_2.sin()
+        - latitude_1.sin() * latitude_2.cos() * delta_longitude.cos();
+
+    /* rem_euclid may round up to exactly 360 for tiny negative values */
+    let bearing = y.atan2(x).to_degrees().rem_euclid(360.0);
+    if bearing >= 360.0 {
+        0.0
+    } else {
+        bearing
+    }
+}
+
+/// Returns the point of a 16-point compass that a bearing is closest to
+///
+/// # Arguments
+///
+/// * `bearing` - Bearing in degrees, clockwise from north
+///
+/// # Returns
+///
+/// Abbreviation of the compass point, e.g. _NNE_
+///
+/// # Examples
+///
+/// * test `position::gets_correct_compass_point`
+pub fn get_compass_point(bearing: f32) -> &'static str {
+    let sector = 360.0 / COMPASS_POINTS.len() as f32;
+    let index = (bearing.rem_euclid(360.0) / sector).round() as usize % COMPASS_POINTS.len();
+
+    COMPASS_POINTS[index]
+}
+
+/// Calculates the line of sight from an observer to a target, taking the curvature of the earth
+/// into account.
+///
+/// # Arguments
+///
+/// * `distance` - Distance of observer and target over ground in km
+/// * `observer_altitude` - Altitude of the observer in m
+/// * `target_altitude` - Altitude of the target in m
+///
+/// # Returns
+///
+/// Tuple of _(slant range, elevation)_: the direct distance in km and the angle above the
+/// horizon in degrees. The elevation is negative if the target is below the horizon.
+///
+/// # Examples
+///
+/// * test `position::calculates_correct_line_of_sight`
+pub fn calculate_line_of_sight(
+    distance: f32,
+    observer_altitude: f32,
+    target_altitude: f32,
+) -> (f32, f32) {
+    /* f32 isn't precise enough for a few meters on top of the earth radius */
+    let earth_radius = f64::from(EARTH_MEAN_RADIUS_KM);
+    let central_angle = f64::from(distance) / earth_radius;
+    let observer_radius = earth_radius + f64::from(observer_altitude) / 1000.0;
+    let target_radius = earth_radius + f64::from(target_altitude) / 1000.0;
+
+    /* Target relative to the observer, along and perpendicular to the observer's horizon */
+    let horizontal = target_radius * central_angle.sin();
+    let vertical = target_radius * central_angle.cos() - observer_radius;
+
+    (
+        horizontal.hypot(vertical) as f32,
+        vertical.atan2(horizontal).to_degrees() as f32,
+    )
+}
+
+/// Area between two latitudes and two longitudes. If `west` is greater than `east`, the area
+/// crosses the antimeridian.
+#[derive(Clone)]
+pub struct BoundingBox {
+    /// Southern latitude
+    pub south: f32,
+    /// Western longitude
+    pub west: f32,
+    /// Northern latitude
+    pub north: f32,
+    /// Eastern longitude
+    pub east: f32,
+}
+
+impl BoundingBox {
+    /// Creates a `BoundingBox` that encloses the circle of a range around a position
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - Center of the circle
+    /// * `range` - Range around `position` in _km_
+    ///
+    /// # Examples
+    ///
+    /// * test `position::bounding_box_around_works`
+    pub fn around(position: &Position, range: f32) -> BoundingBox {
+        let delta_latitude = (range / EARTH_MEAN_RADIUS_KM).to_degrees();
+        let south = position.latitude - delta_latitude;
+        let north = position.latitude + delta_latitude;
+
+        if south <= -90.0 || north >= 90.0 {
+            /* Circle contains a pole, so it spans all longitudes */
+            return BoundingBox {
+                south: south.max(-90.0),
+                west: -180.0,
+                north: north.min(90.0),
+                east: 180.0,
+            };
+        }
+
+        /* Degrees of longitude are shortest at the latitude that is closest to a pole */
+        let delta_longitude = delta_latitude / south.abs().max(north.abs()).to_radians().cos();
+
+        if delta_longitude >= 180.0 {
+            return BoundingBox {
+                south,
+                west: -180.0,
+                north,
+                east: 180.0,
+            };
+        }
+
+        BoundingBox {
+            south,
+            west: normalize_longitude(position.longitude - delta_longitude),
+            north,
+            east: normalize_longitude(position.longitude + delta_longitude),
+        }
+    }
+
+    /// Checks whether a position is inside of the `BoundingBox`
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The position to check
+    ///
+    /// # Examples
+    ///
+    /// * test `position::checks_bounding_box_correctly`
+    pub fn contains(&self, position: &Position) -> bool {
+        if position.latitude < self.south || position.latitude > self.north {
+            return false;
+        }
+
+        if self.west <= self.east {
+            position.longitude >= self.west && position.longitude <= self.east
+        } else {
+            position.longitude >= self.west || position.longitude <= self.east
+        }
+    }
+}
+
+/// Polygon with optional holes. Longitudes of the corners are unwrapped, so that edges that
+/// cross the antimeridian don't span the whole globe.
+#[derive(Clone, Deserialize)]
+#[serde(from = "Vec<Position>")]
+pub struct Polygon {
+    /// Rings of corners, in order. The first ring is the outer boundary, all others are holes.
+    /// The last corner of a ring is connected to the first.
+    rings: Vec<Vec<Position>>,
+}
+
+impl Polygon {
+    /// Creates a `Polygon`
+    ///
+    /// # Arguments
+    ///
+    /// * `rings` - Rings of corners, in order. The first ring is the outer boundary, all others
+    ///   are holes.
+    ///
+    /// # Examples
+    ///
+    /// * test `position::checks_polygon_correctly`
+    pub fn create(rings: Vec<Vec<Position>>) -> Polygon {
+        let mut previous_longitude = rings
+            .first()
+            .and_then(|ring| ring.first())
+            .map_or(0.0, |corner| corner.longitude);
+
+        let rings = rings
+            .into_iter()
+            .map(|ring| {
+                ring.into_iter()
+                    .map(|corner| {
+                        let longitude = previous_longitude
+                            + normalize_longitude(corner.longitude - previous_longitude);
+                        previous_longitude = longitude;
+
+                        Position {
+                            latitude: corner.latitude,
+                            longitude,
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Polygon { rings }
+    }
+
+    /// Returns the `BoundingBox` of the `Polygon`
+    ///
+    /// # Returns
+    ///
+    /// The `BoundingBox` or `None` if the `Polygon` has no corners
+    ///
+    /// # Examples
+    ///
+    /// * test `position::checks_polygon_across_antimeridian_correctly`
+    pub fn get_bounding_box(&self) -> Option<BoundingBox> {
+        let boundary = self.rings.first().filter(|ring| !ring.is_empty())?;
+
+        let mut bounding_box = BoundingBox {
+            south: f32::MAX,
+            west: f32::MAX,
+            north: f32::MIN,
+            east: f32::MIN,
+        };
+
+        for corner in boundary {
+            bounding_box.south = bounding_box.south.min(corner.latitude);
+            bounding_box.west = bounding_box.west.min(corner.longitude);
+            bounding_box.north = bounding_box.north.max(corner.latitude);
+            bounding_box.east = bounding_box.east.max(corner.longitude);
+        }
+
+        /* Longitudes of the corners are unwrapped and may exceed ±180° */
+        if bounding_box.east - bounding_box.west >= 360.0 {
+            bounding_box.west = -180.0;
+            bounding_box.east = 180.0;
+        } else {
+            bounding_box.west = normalize_longitude(bounding_box.west);
+            bounding_box.east = normalize_longitude(bounding_box.east);
+        }
+
+        Some(bounding_box)
+    }
+
+    /// Checks whether a position is inside of the `Polygon` (and not inside of one of its
+    /// holes). Positions on the edges may count as inside or outside.
+    ///
+    /// # Arguments
+    ///
+    /// * `position` - The position to check
+    ///
+    /// # Examples
+    ///
+    /// * test `position::checks_polygon_correctly`
+    /// * test `position::checks_polygon_across_antimeridian_correctly`
+    pub fn contains(&self, position: &Position) -> bool {
+        let Some((boundary, holes)) = self.rings.split_first() else {
+            return false;
+        };
+
+        /* Unwrapped longitudes may exceed ±180°, so also check the position one turn east- and
+         * westwards */
+        [0.0, 360.0, -360.0].iter().any(|offset| {
+            let longitude = position.longitude + offset;
+
+            is_in_ring(position.latitude, longitude, boundary)
+                && !holes
+                    .iter()
+                    .any(|hole| is_in_ring(position.latitude, longitude, hole))
+        })
+    }
+}
+
+impl From<Vec<Position>> for Polygon {
+    fn from(corners: Vec<Position>) -> Self {
+        Polygon::create(vec![corners])
+    }
+}
+
+/// Checks whether a position is inside of a ring of corners
+///
+/// # Arguments
+///
+/// * `latitude` - Latitude of the position
+/// * `longitude` - Longitude of the position
+/// * `ring` - Corners of the ring, in order. The last corner is connected to the first.
+///
+/// # Resources
+///
+/// * [wrfranklin.org](https://wrfranklin.org/Research/Short_Notes/pnpoly.html)
+fn is_in_ring(latitude: f32, longitude: f32, ring: &[Position]) -> bool {
+    let mut is_inside = false;
+
+    /* Cast a ray from the position eastwards and count the edges that it crosses */
+    for (i, corner) in ring.iter().enumerate() {
+        let previous_corner = &ring[(i + ring.len() - 1) % ring.len()];
+
+        if (corner.latitude > latitude) != (previous_corner.latitude > latitude)
+            && longitude
+                < (previous_corner.longitude - corner.longitude) * (latitude - corner.latitude)
+                    / (previous_corner.latitude - corner.latitude)
+                    + corner.longitude
+        {
+            is_inside = !is_inside;
+        }
+    }
+
+    is_inside
+}
+
+/// Normalizes a longitude (difference) to the range of -180° to 180°
+///
+/// # Arguments
+///
+/// * `longitude` - The longitude to normalize
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(normalize_longitude(190.0), -170.0);
+/// ```
+fn normalize_longitude(longitude: f32) -> f32 {
+    (longitude + 180.0).rem_euclid(360.0) - 180.0
+}
+
 impl Display for Position {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
         write!(
@@ -87,4 +498,241 @@ mod tests {
         assert_eq!(calculate_distance(&pos1, &pos2), 3.1636212);
         assert_eq!(calculate_distance(&pos2, &pos1), 3.1636212);
     }
+
+    #[test]
+    fn calculates_correct_bearing() {
+        let pos1 = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        let pos2 = Position {
+            latitude: 48.86055,
+            longitude: 2.3376,
+        };
+
+        assert!((calculate_bearing(&pos1, &pos2) - 85.29).abs() < 0.01);
+        assert!((calculate_bearing(&pos2, &pos1) - 265.32).abs() < 0.01);
+
+        let north = create_position((49.0, 2.2945));
+        let south = create_position((48.0, 2.2945));
+        assert_eq!(calculate_bearing(&pos1, &north), 0.0);
+        assert_eq!(calculate_bearing(&pos1, &south), 180.0);
+
+        /* Across the antimeridian */
+        let bearing = calculate_bearing(
+            &create_position((0.0, 179.9)),
+            &create_position((0.0, -179.9)),
+        );
+        assert!((bearing - 90.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn gets_correct_compass_point() {
+        assert_eq!(get_compass_point(0.0), "N");
+        assert_eq!(get_compass_point(11.0), "N");
+        assert_eq!(get_compass_point(12.0), "NNE");
+        assert_eq!(get_compass_point(45.0), "NE");
+        assert_eq!(get_compass_point(85.29), "E");
+        assert_eq!(get_compass_point(200.0), "SSW");
+        assert_eq!(get_compass_point(350.0), "N");
+        assert_eq!(get_compass_point(-90.0), "W");
+    }
+
+    #[test]
+    fn calculates_correct_line_of_sight() {
+        /* 1 km above, 10 km away: the earth's curvature lowers the angle by about 0.05° */
+        let (slant_range, elevation) = calculate_line_of_sight(10.0, 0.0, 1000.0);
+        assert!((slant_range - 10.0507).abs() < 0.001);
+        assert!((elevation - 5.6652).abs() < 0.001);
+
+        /* Directly above */
+        let (slant_range, elevation) = calculate_line_of_sight(0.0, 500.0, 1500.0);
+        assert!((slant_range - 1.0).abs() < 0.001);
+        assert_eq!(elevation, 90.0);
+
+        /* Same altitude is always below the horizon */
+        let (_, elevation) = calculate_line_of_sight(50.0, 1000.0, 1000.0);
+        assert!(elevation < 0.0);
+    }
+
+    #[test]
+    fn calculates_correct_offset() {
+        let origin = Position {
+            latitude: 48.858222,
+            longitude: 2.2945,
+        };
+
+        let position = Position {
+            latitude: 48.86055,
+            longitude: 2.3376,
+        };
+
+        let (north, east) = calculate_offset(&origin, &position);
+        assert!(north > 0.0 && east > 0.0);
+        assert!((north.hypot(east) - calculate_distance(&origin, &position)).abs() < 0.01);
+
+        let (north, east) = calculate_offset(&position, &origin);
+        assert!(north < 0.0 && east < 0.0);
+
+        let origin = Position {
+            latitude: 0.0,
+            longitude: 179.9,
+        };
+
+        let position = Position {
+            latitude: 0.0,
+            longitude: -179.9,
+        };
+
+        let (north, east) = calculate_offset(&origin, &position);
+        assert!(north.abs() < 0.01);
+        assert!((east - 22.238).abs() < 0.01);
+    }
+
+    #[test]
+    fn checks_polygon_correctly() {
+        let polygon = Polygon::from(vec![
+            Position {
+                latitude: 48.0,
+                longitude: 2.0,
+            },
+            Position {
+                latitude: 48.0,
+                longitude: 3.0,
+            },
+            Position {
+                latitude: 49.0,
+                longitude: 3.0,
+            },
+            /* Notch, so that the polygon is concave */
+            Position {
+                latitude: 48.5,
+                longitude: 2.5,
+            },
+            Position {
+                latitude: 49.0,
+                longitude: 2.0,
+            },
+        ]);
+
+        let inside = Position {
+            latitude: 48.25,
+            longitude: 2.5,
+        };
+
+        let in_notch = Position {
+            latitude: 48.9,
+            longitude: 2.5,
+        };
+
+        let outside = Position {
+            latitude: 47.5,
+            longitude: 2.5,
+        };
+
+        assert!(polygon.contains(&inside));
+        assert!(!polygon.contains(&in_notch));
+        assert!(!polygon.contains(&outside));
+        assert!(!Polygon::create(vec![]).contains(&inside));
+    }
+
+    #[test]
+    fn checks_polygon_across_antimeridian_correctly() {
+        let corners = [
+            (-10.0, 170.0),
+            (-10.0, -170.0),
+            (10.0, -170.0),
+            (10.0, 170.0),
+        ];
+        let hole = [(-1.0, 179.0), (-1.0, -179.0), (1.0, -179.0), (1.0, 179.0)];
+
+        let sut = Polygon::create(vec![
+            corners.iter().map(|&c| create_position(c)).collect(),
+            hole.iter().map(|&c| create_position(c)).collect(),
+        ]);
+
+        assert!(sut.contains(&create_position((5.0, 175.0))));
+        assert!(sut.contains(&create_position((5.0, -175.0))));
+        assert!(sut.contains(&create_position((-5.0, 180.0))));
+        assert!(!sut.contains(&create_position((0.0, 180.0))));
+        assert!(!sut.contains(&create_position((0.0, -179.5))));
+        assert!(!sut.contains(&create_position((5.0, 0.0))));
+        assert!(!sut.contains(&create_position((5.0, 165.0))));
+
+        let bounding_box = sut.get_bounding_box().unwrap();
+        assert_eq!(bounding_box.south, -10.0);
+        assert_eq!(bounding_box.west, 170.0);
+        assert_eq!(bounding_box.north, 10.0);
+        assert_eq!(bounding_box.east, -170.0);
+        assert!(Polygon::create(vec![]).get_bounding_box().is_none());
+    }
+
+    #[test]
+    fn bounding_box_around_works() {
+        let position = create_position((48.858222, 2.2945));
+        let sut = BoundingBox::around(&position, 5.0);
+
+        assert!(sut.contains(&position));
+        /* see calculates_correct_distance -> 3.16 km */
+        assert!(sut.contains(&create_position((48.86055, 2.3376))));
+        assert!(!sut.contains(&create_position((48.95, 2.2945))));
+        assert!(!sut.contains(&create_position((48.858222, 2.4))));
+
+        let across_antimeridian = BoundingBox::around(&create_position((0.0, 179.99)), 5.0);
+        assert!(across_antimeridian.west > across_antimeridian.east);
+        assert!(across_antimeridian.contains(&create_position((0.0, -179.99))));
+
+        let around_pole = BoundingBox::around(&create_position((89.99, 0.0)), 5.0);
+        assert!(around_pole.contains(&create_position((89.99, 180.0))));
+    }
+
+    #[test]
+    fn calculates_correct_position_by_offset() {
+        let origin = create_position((48.858222, 2.2945));
+        let position = create_position((48.86055, 2.3376));
+
+        let (north, east) = calculate_offset(&origin, &position);
+        let result = calculate_position_by_offset(&origin, north, east);
+
+        assert!((result.latitude - position.latitude).abs() < 0.0001);
+        assert!((result.longitude - position.longitude).abs() < 0.0001);
+
+        /* Across the antimeridian */
+        let result = calculate_position_by_offset(&create_position((0.0, 179.9)), 0.0, 22.239);
+        assert!((result.longitude + 179.9).abs() < 0.0001);
+    }
+
+    #[test]
+    fn checks_bounding_box_correctly() {
+        let sut = BoundingBox {
+            south: 48.0,
+            west: 2.0,
+            north: 49.0,
+            east: 3.0,
+        };
+
+        assert!(sut.contains(&create_position((48.5, 2.5))));
+        assert!(!sut.contains(&create_position((47.5, 2.5))));
+        assert!(!sut.contains(&create_position((48.5, 3.5))));
+
+        let across_antimeridian = BoundingBox {
+            south: -10.0,
+            west: 170.0,
+            north: 10.0,
+            east: -170.0,
+        };
+
+        assert!(across_antimeridian.contains(&create_position((0.0, 175.0))));
+        assert!(across_antimeridian.contains(&create_position((0.0, -175.0))));
+        assert!(!across_antimeridian.contains(&create_position((0.0, 0.0))));
+        assert!(!across_antimeridian.contains(&create_position((0.0, 165.0))));
+    }
+
+    fn create_position((latitude, longitude): (f32, f32)) -> Position {
+        Position {
+            latitude,
+            longitude,
+        }
+    }
 }
@@ -14,6 +14,62 @@ pub fn get_current_timestamp() -> u64 {
         .as_secs()
 }
 
+/// Date and time (UTC) of a unix timestamp
+pub struct DateTime {
+    /// Year, e.g. 2024
+    pub year: u64,
+    /// Month, 1 - 12
+    pub month: u64,
+    /// Day of month, 1 - 31
+    pub day: u64,
+    /// Hour, 0 - 23
+    pub hour: u64,
+    /// Minute, 0 - 59
+    pub minute: u64,
+    /// Second, 0 - 59
+    pub second: u64,
+}
+
+/// Converts a unix timestamp into date and time (UTC)
+///
+/// # Arguments
+///
+/// * `timestamp` - The unix timestamp
+///
+/// # Examples
+///
+/// * test `time::get_date_time_works`
+///
+/// # Resources
+///
+/// * [howardhinnant.github.io](https://howardhinnant.github.io/date_algorithms.html#civil_from_days)
+pub fn get_date_time(timestamp: u64) -> DateTime {
+    let seconds_of_day = timestamp % 86400;
+
+    /* Shift epoch from 1970-01-01 to 0000-03-01, so that leap days are at the end of a year */
+    let days = timestamp / 86400 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+
+    DateTime {
+        year: year_of_era + era * 400 + u64::from(month <= 2),
+        month,
+        day: day_of_year - (153 * shifted_month + 2) / 5 + 1,
+        hour: seconds_of_day / 3600,
+        minute: seconds_of_day % 3600 / 60,
+        second: seconds_of_day % 60,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -24,4 +80,20 @@ mod tests {
     fn timestamp_not_empty() {
         assert!(get_current_timestamp() > 0);
     }
+
+    #[test]
+    fn get_date_time_works() {
+        let result = get_date_time(0);
+        assert_eq!((result.year, result.month, result.day), (1970, 1, 1));
+        assert_eq!((result.hour, result.minute, result.second), (0, 0, 0));
+
+        /* Leap day */
+        let result = get_date_time(1_709_210_096);
+        assert_eq!((result.year, result.month, result.day), (2024, 2, 29));
+        assert_eq!((result.hour, result.minute, result.second), (12, 34, 56));
+
+        let result = get_date_time(1_735_689_599);
+        assert_eq!((result.year, result.month, result.day), (2024, 12, 31));
+        assert_eq!((result.hour, result.minute, result.second), (23, 59, 59));
+    }
 }